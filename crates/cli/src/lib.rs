@@ -0,0 +1,19 @@
+/// The first command line argument that is not a `--flag`, which names where the input comes from.
+pub fn source() -> Option<String> {
+    std::env::args().skip(1).find(|arg| !arg.starts_with("--"))
+}
+
+/// Whether the bare `--name` flag was passed on the command line.
+pub fn has_flag(name: &str) -> bool {
+    let flag = format!("--{}", name);
+    std::env::args().skip(1).any(|arg| arg == flag)
+}
+
+/// Values of every `--name=value` flag passed on the command line, in order.
+pub fn flags(name: &str) -> Vec<String> {
+    let prefix = format!("--{}=", name);
+    std::env::args()
+        .skip(1)
+        .filter_map(|arg| arg.strip_prefix(&prefix).map(str::to_string))
+        .collect()
+}
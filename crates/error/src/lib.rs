@@ -0,0 +1,114 @@
+use input::Input;
+use snafu::{OptionExt, ResultExt, Snafu};
+use snafu_cli_debug::SnafuCliDebug;
+use std::io::{self, BufRead, BufReader};
+
+pub use parse;
+
+#[derive(Snafu, SnafuCliDebug)]
+#[snafu(visibility = "pub")]
+pub enum Error {
+    /// Error loading input
+    LoadingInput { source: input::Error },
+    /// Error parsing input
+    Parsing { source: parse::Error },
+    #[snafu(display("No answer found for part {}", part))]
+    MissingAnswer { part: u8 },
+}
+
+/// Loads the normalized input for `day`.
+///
+/// The first command line argument that is not a `--flag` selects the source: `-` reads stdin, any other
+/// value is read as a file, and without one the input is fetched using the settings in `config.toml`.
+pub fn load(day: u8) -> Result<String, Error> {
+    match cli::source().as_deref() {
+        Some("-") => input::read_stdin(),
+        Some(path) => input::read_file(path),
+        None => Input::open("config.toml").and_then(|input| input.get(day)),
+    }
+    .context(LoadingInput)
+}
+
+/// Loads the input for `day` like [`load`], but without normalizing it.
+pub fn load_raw(day: u8) -> Result<Vec<u8>, Error> {
+    match cli::source().as_deref() {
+        Some("-") => input::read_stdin_raw(),
+        Some(path) => input::read_file_raw(path),
+        None => Input::open("config.toml").and_then(|input| input.get_raw(day)),
+    }
+    .context(LoadingInput)
+}
+
+/// Opens the input for `day` like [`load`], but as an unnormalized reader so large files and stdin are
+/// streamed rather than read whole. Inputs fetched using `config.toml` are still read into memory.
+pub fn open(day: u8) -> Result<Box<dyn BufRead>, Error> {
+    let reader: Box<dyn BufRead> = match cli::source().as_deref() {
+        Some("-") => Box::new(BufReader::new(io::stdin())),
+        Some(path) => Box::new(input::open_file(path).context(LoadingInput)?),
+        None => Box::new(io::Cursor::new(
+            Input::open("config.toml").and_then(|input| input.get_raw(day)).context(LoadingInput)?,
+        )),
+    };
+    Ok(reader)
+}
+
+impl From<parse::Error> for Error {
+    fn from(source: parse::Error) -> Self {
+        Error::Parsing { source }
+    }
+}
+
+pub trait AnswerExt<T> {
+    /// Converts a missing answer into [`Error::MissingAnswer`] for `part`.
+    fn answer(self, part: u8) -> Result<T, Error>;
+}
+
+impl<T> AnswerExt<T> for Option<T> {
+    fn answer(self, part: u8) -> Result<T, Error> {
+        self.context(MissingAnswer { part })
+    }
+}
+
+/// Declares a day's `Error` enum holding the shared [`Error`] alongside the day specific variants.
+///
+/// The day crate still needs `snafu` and `snafu-cli-debug` as dependencies for the derives.
+#[macro_export]
+macro_rules! day_error {
+    ($($variants:tt)*) => {
+        #[derive(snafu::Snafu, snafu_cli_debug::SnafuCliDebug)]
+        pub enum Error {
+            /// Error solving puzzle
+            Solver { source: $crate::Error },
+            $($variants)*
+        }
+
+        impl From<$crate::Error> for Error {
+            fn from(source: $crate::Error) -> Self {
+                Error::Solver { source }
+            }
+        }
+
+        impl From<$crate::parse::Error> for Error {
+            fn from(source: $crate::parse::Error) -> Self {
+                Error::Solver { source: source.into() }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_parse() {
+        let error: Error = parse::parse_lines::<isize>("x", "integer").unwrap_err().into();
+        assert!(matches!(error, Error::Parsing { .. }));
+    }
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Some(5).answer(1).unwrap(), 5);
+        assert!(matches!(None::<usize>.answer(2), Err(Error::MissingAnswer { part: 2 })));
+    }
+}
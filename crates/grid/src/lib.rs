@@ -0,0 +1,315 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Which cells count as neighbours of a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, down, left and right
+    Four,
+    /// The four orthogonal neighbours plus the diagonals
+    Eight,
+}
+
+impl Connectivity {
+    fn offsets(self) -> &'static [(isize, isize)] {
+        const FOUR: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+        const EIGHT: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+        match self {
+            Connectivity::Four => &FOUR,
+            Connectivity::Eight => &EIGHT,
+        }
+    }
+}
+
+/// How [`Grid::parse_ragged`] handles rows narrower or wider than others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ragged<T> {
+    /// Every row has to be as wide as the first, like [`Grid::parse`]
+    Reject,
+    /// Narrower rows are filled up to the widest with the given cell
+    Pad(T),
+    /// Wider rows are cut down to the narrowest
+    Truncate,
+}
+
+/// Rectangular grid of cells stored row by row in one buffer, indexed by `(x, y)` from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from `cells` in row order.
+    ///
+    /// Panics when `cells` does not hold exactly `width * height` cells.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height, "{}x{} grid needs {} cells", width, height, width * height);
+        Self { width, height, cells }
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(width, height, vec![value; width * height])
+    }
+
+    /// Parses a character map, one row per line, mapping every character with `cell`.
+    ///
+    /// Characters `cell` rejects are reported as `expected`, and every row has to be as wide as the first.
+    pub fn parse<F>(input: &str, expected: &str, mut cell: F) -> Result<Self, parse::Error>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+        for line in parse::lines(input) {
+            let mut count = 0;
+            for (offset, c) in line.text().char_indices() {
+                if Some(count) == width {
+                    return Err(line.error_at(offset, line.text().len() - offset, "end of row"));
+                }
+                cells.push(cell(c).ok_or_else(|| line.error_at(offset, c.len_utf8(), expected))?);
+                count += 1;
+            }
+            match width {
+                Some(width) if count < width => return Err(line.error_at_end(format!("{} cells", width))),
+                Some(_) => {}
+                None => width = Some(count),
+            }
+            height += 1;
+        }
+        Ok(Self::new(width.unwrap_or(0), height, cells))
+    }
+
+    /// Parses a character map like [`Grid::parse`], making rows of different widths rectangular as
+    /// `ragged` says.
+    pub fn parse_ragged<F>(input: &str, expected: &str, ragged: Ragged<T>, mut cell: F) -> Result<Self, parse::Error>
+    where
+        T: Clone,
+        F: FnMut(char) -> Option<T>,
+    {
+        if let Ragged::Reject = ragged {
+            return Self::parse(input, expected, cell);
+        }
+        let rows = parse::lines(input)
+            .map(|line| {
+                line.text()
+                    .char_indices()
+                    .map(|(offset, c)| cell(c).ok_or_else(|| line.error_at(offset, c.len_utf8(), expected)))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let widths = rows.iter().map(Vec::len);
+        let width = match ragged {
+            Ragged::Pad(_) => widths.max(),
+            _ => widths.min(),
+        }
+        .unwrap_or(0);
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for mut row in rows {
+            match &ragged {
+                Ragged::Pad(fill) => row.resize(width, fill.clone()),
+                _ => row.truncate(width),
+            }
+            cells.extend(row);
+        }
+        Ok(Self::new(width, height, cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if self.contains(x, y) {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if self.contains(x, y) {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Toroidal indexing: coordinates outside the grid wrap around, in both directions.
+    ///
+    /// Panics on an empty grid.
+    pub fn wrapping(&self, x: isize, y: isize) -> &T {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        &self.cells[y * self.width + x]
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on a zero size, and a zero width grid has no cells to split anyway
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {} outside a grid {} wide", x, self.width);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Every cell with its coordinates, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+        self.cells.iter().enumerate().map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+
+    /// Coordinates of the neighbours of `(x, y)` that lie inside the grid.
+    pub fn neighbours(&self, x: usize, y: usize, connectivity: Connectivity) -> impl Iterator<Item = (usize, usize)> + '_ {
+        connectivity.offsets().iter().filter_map(move |(dx, dy)| {
+            let x = (x as isize + dx) as usize;
+            let y = (y as isize + dy) as usize;
+            if self.contains(x, y) {
+                Some((x, y))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Renders one character per cell, one line per row.
+    pub fn render<F>(&self, mut cell: F) -> String
+    where
+        F: FnMut(&T) -> char,
+    {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            out.extend(row.iter().map(&mut cell));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) outside {}x{} grid", x, y, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(x, y).unwrap_or_else(|| panic!("({}, {}) outside {}x{} grid", x, y, width, height))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse(input, "digit", |c| c.to_digit(10)).expect("parse")
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits("123\r\n456\n");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(Grid::parse("", "digit", |c| c.to_digit(10)).expect("parse").height(), 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = Grid::parse("12\n1x", "digit", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((error.line, error.column, error.expected.as_str()), (2, 2, "digit"));
+        let error = Grid::parse("12\n1", "digit", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((error.line, error.column, error.expected.as_str()), (2, 2, "2 cells"));
+        let error = Grid::parse("12\n1234", "digit", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((error.line, error.column, error.found.as_str()), (2, 3, "34"));
+    }
+
+    #[test]
+    fn test_parse_ragged() {
+        let input = "12\n3\n456";
+        let padded = Grid::parse_ragged(input, "digit", Ragged::Pad(0), |c| c.to_digit(10)).expect("parse");
+        assert_eq!(padded.to_string(), "120\n300\n456\n");
+        let truncated = Grid::parse_ragged(input, "digit", Ragged::Truncate, |c| c.to_digit(10)).expect("parse");
+        assert_eq!(truncated.to_string(), "1\n3\n4\n");
+        let error = Grid::parse_ragged(input, "digit", Ragged::Reject, |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((error.line, error.expected.as_str()), (2, "2 cells"));
+        let error = Grid::parse_ragged("12\n3y", "digit", Ragged::Pad(0), |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let grid = digits("123\n456");
+        assert_eq!(*grid.wrapping(3, 0), 1);
+        assert_eq!(*grid.wrapping(-1, -1), 6);
+        assert_eq!(*grid.wrapping(7, 5), 5);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let mut grid = digits("123\n456");
+        grid[(0, 0)] = 9;
+        assert_eq!(grid.rows().collect::<Vec<_>>(), vec![&[9, 2, 3][..], &[4, 5, 6][..]]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(grid.columns().map(|column| column.sum::<u32>()).collect::<Vec<_>>(), vec![13, 7, 9]);
+        assert_eq!(grid.iter().nth(4), Some(((1, 1), &5)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = digits("123\n456\n789");
+        let four = grid.neighbours(0, 0, Connectivity::Four).collect::<Vec<_>>();
+        assert_eq!(four, vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbours(1, 1, Connectivity::Eight).count(), 8);
+        let sum = grid.neighbours(2, 1, Connectivity::Eight).map(|p| grid[p]).sum::<u32>();
+        assert_eq!(sum, 2 + 3 + 5 + 8 + 9);
+    }
+
+    #[test]
+    fn test_display() {
+        let grid = digits("12\n34");
+        assert_eq!(grid.to_string(), "12\n34\n");
+        assert_eq!(grid.render(|n| if n % 2 == 0 { '#' } else { '.' }), ".#\n.#\n");
+        assert_eq!(Grid::filled(2, 1, 'x').to_string(), "xx\n");
+    }
+}
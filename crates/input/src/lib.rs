@@ -1,7 +1,7 @@
-use attohttpc;
 use config::{Config, File};
 use snafu::{ResultExt, Snafu};
 use std::fs;
+use std::io::{self, BufReader, Read};
 use std::path::{Path, PathBuf};
 use url::Url;
 
@@ -12,6 +12,50 @@ pub enum Error {
     HttpGet { source: attohttpc::Error },
     Caching { source: std::io::Error, path: String },
     GetFailed { status: String },
+    Reading { source: std::io::Error, path: String },
+    Decoding { source: std::string::FromUtf8Error },
+}
+
+/// Strips a leading byte order mark, converts `\r\n` line endings to `\n` and removes trailing newlines.
+pub fn normalize(raw: &str) -> String {
+    let text = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    text.replace("\r\n", "\n").trim_end_matches('\n').to_string()
+}
+
+fn decode(raw: Vec<u8>) -> Result<String, Error> {
+    Ok(normalize(&String::from_utf8(raw).context(Decoding)?))
+}
+
+/// Reads the file at `path` without any normalization.
+pub fn read_file_raw<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, Error> {
+    fs::read(path.as_ref()).context(Reading {
+        path: path.as_ref().to_string_lossy(),
+    })
+}
+
+/// Opens the file at `path` for buffered reading, for inputs too large to read whole.
+pub fn open_file<P: AsRef<Path>>(path: P) -> Result<BufReader<fs::File>, Error> {
+    let file = fs::File::open(path.as_ref()).context(Reading {
+        path: path.as_ref().to_string_lossy(),
+    })?;
+    Ok(BufReader::new(file))
+}
+
+/// Reads the file at `path` as normalized text.
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    decode(read_file_raw(path)?)
+}
+
+/// Reads all of stdin without any normalization.
+pub fn read_stdin_raw() -> Result<Vec<u8>, Error> {
+    let mut raw = Vec::new();
+    io::stdin().read_to_end(&mut raw).context(Reading { path: "<stdin>" })?;
+    Ok(raw)
+}
+
+/// Reads all of stdin as normalized text.
+pub fn read_stdin() -> Result<String, Error> {
+    decode(read_stdin_raw()?)
 }
 
 pub struct Input {
@@ -37,11 +81,17 @@ impl Input {
         })
     }
 
+    /// Gets the input for `day` as normalized text.
     pub fn get(&self, day: u8) -> Result<String, Error> {
+        decode(self.get_raw(day)?)
+    }
+
+    /// Gets the input for `day` exactly as downloaded, for days that need the raw bytes.
+    pub fn get_raw(&self, day: u8) -> Result<Vec<u8>, Error> {
         let day = day.to_string();
         let input_path = self.cache_path.join(Path::new(&day)).join("input");
         let dir_path = self.cache_path.join(Path::new(&day));
-        match fs::read_to_string(&input_path) {
+        match fs::read(&input_path) {
             Ok(input) => Ok(input),
             Err(_) => {
                 let mut new_url = self.url.clone();
@@ -51,7 +101,7 @@ impl Input {
                     .send()
                     .context(HttpGet)?;
                 if input.is_success() {
-                    let input = input.text().context(HttpGet)?;
+                    let input = input.bytes().context(HttpGet)?;
                     fs::create_dir_all(&dir_path).context(Caching {
                         path: dir_path.to_string_lossy(),
                     })?;
@@ -68,3 +118,22 @@ impl Input {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("\u{feff}..#\r\n#..\r\n"), "..#\n#..");
+        assert_eq!(normalize("a\n\nb\n\n\n"), "a\n\nb");
+        assert_eq!(normalize("a\rb"), "a\rb");
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"\xef\xbb\xbfFBFBBFFRLR\r\n".to_vec()).unwrap(), "FBFBBFFRLR");
+        assert!(decode(vec![0xff, 0xfe]).is_err());
+    }
+}
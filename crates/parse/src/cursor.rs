@@ -0,0 +1,255 @@
+use crate::{Error, Line};
+use std::str::FromStr;
+
+/// Zero-copy scanner over a single [`Line`], consuming tokens from the front.
+///
+/// Every combinator either returns a slice borrowed from the input or an [`Error`]
+/// pointing at the position where it failed. A failed combinator leaves the cursor where it was.
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'a> {
+    line: Line<'a>,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(line: Line<'a>) -> Self {
+        Self { line, pos: 0 }
+    }
+
+    pub fn line(&self) -> Line<'a> {
+        self.line
+    }
+
+    /// Byte offset of the cursor within the line.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The unconsumed remainder of the line.
+    pub fn rest(&self) -> &'a str {
+        &self.line.text()[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    /// Builds an error pointing at the next character.
+    pub fn error(&self, expected: impl Into<String>) -> Error {
+        let len = self.rest().chars().next().map(char::len_utf8).unwrap_or(0);
+        self.line.error_at(self.pos, len, expected)
+    }
+
+    fn advance(&mut self, len: usize) -> &'a str {
+        let token = &self.rest()[..len];
+        self.pos += len;
+        token
+    }
+
+    /// Consumes `tag` if the remainder starts with it.
+    pub fn eat(&mut self, tag: &str) -> bool {
+        if self.rest().starts_with(tag) {
+            self.pos += tag.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes exactly `tag`.
+    pub fn tag(&mut self, tag: &str) -> Result<&'a str, Error> {
+        if self.rest().starts_with(tag) {
+            Ok(self.advance(tag.len()))
+        } else {
+            Err(self.error(format!("`{}`", tag)))
+        }
+    }
+
+    /// Consumes characters while `f` holds, possibly none.
+    pub fn take_while<F: FnMut(char) -> bool>(&mut self, mut f: F) -> &'a str {
+        let len = self.rest().find(|c| !f(c)).unwrap_or_else(|| self.rest().len());
+        self.advance(len)
+    }
+
+    /// Consumes at least one character while `f` holds.
+    pub fn take_while1<F: FnMut(char) -> bool>(&mut self, f: F, expected: &str) -> Result<&'a str, Error> {
+        let start = *self;
+        let token = self.take_while(f);
+        if token.is_empty() {
+            *self = start;
+            Err(self.error(expected))
+        } else {
+            Ok(token)
+        }
+    }
+
+    /// Consumes everything up to, but not including, the next occurrence of `pattern`.
+    pub fn until(&mut self, pattern: &str) -> Result<&'a str, Error> {
+        match self.rest().find(pattern) {
+            Some(len) => Ok(self.advance(len)),
+            None => Err(self.line.error_at(self.pos, self.rest().len(), format!("text followed by `{}`", pattern))),
+        }
+    }
+
+    /// Consumes the rest of the line.
+    pub fn remainder(&mut self) -> &'a str {
+        self.advance(self.rest().len())
+    }
+
+    pub fn whitespace(&mut self) -> &'a str {
+        self.take_while(char::is_whitespace)
+    }
+
+    /// Consumes a run of non-whitespace characters.
+    pub fn word(&mut self) -> Result<&'a str, Error> {
+        self.take_while1(|c| !c.is_whitespace(), "word")
+    }
+
+    /// Consumes a single character.
+    pub fn char(&mut self) -> Result<char, Error> {
+        self.char_if(|_| true, "character")
+    }
+
+    /// Consumes a single character for which `f` holds.
+    pub fn char_if<F: FnOnce(char) -> bool>(&mut self, f: F, expected: &str) -> Result<char, Error> {
+        match self.rest().chars().next() {
+            Some(c) if f(c) => {
+                self.pos += c.len_utf8();
+                Ok(c)
+            }
+            _ => Err(self.error(expected)),
+        }
+    }
+
+    /// Consumes a run of decimal digits.
+    pub fn unsigned<T: FromStr>(&mut self) -> Result<T, Error> {
+        let start = *self;
+        let digits = self.take_while1(|c| c.is_ascii_digit(), "unsigned integer")?;
+        digits.parse::<T>().map_err(|_| {
+            *self = start;
+            self.line.error(digits, "unsigned integer in range")
+        })
+    }
+
+    /// Consumes an optional `+` or `-` sign followed by decimal digits.
+    pub fn signed<T: FromStr>(&mut self) -> Result<T, Error> {
+        let start = *self;
+        if !self.eat("-") {
+            self.eat("+");
+        }
+        if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+            *self = start;
+            return Err(self.error("signed integer"));
+        }
+        let token = &start.rest()[..self.pos - start.pos];
+        token.parse::<T>().map_err(|_| {
+            *self = start;
+            self.line.error(token, "signed integer in range")
+        })
+    }
+
+    /// Consumes a `key<separator>value` pair, where the value runs up to the next whitespace.
+    pub fn key_value(&mut self, separator: char) -> Result<(&'a str, &'a str), Error> {
+        let start = *self;
+        let key = self.take_while1(|c| c != separator && !c.is_whitespace(), "key")?;
+        let expected = format!("`{}`", separator);
+        if let Err(error) = self.char_if(|c| c == separator, &expected) {
+            *self = start;
+            return Err(error);
+        }
+        let value = self.take_while(|c| !c.is_whitespace());
+        Ok((key, value))
+    }
+
+    /// Consumes one or more items produced by `f`, separated by `separator`.
+    pub fn delimited<T, F>(&mut self, separator: &str, mut f: F) -> Result<Vec<T>, Error>
+    where
+        F: FnMut(&mut Self) -> Result<T, Error>,
+    {
+        let mut items = vec![f(self)?];
+        while self.eat(separator) {
+            items.push(f(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Consumes an `N <name> <noun>` quantity such as `2 muted yellow bags`, accepting a plural `s`.
+    pub fn quantity(&mut self, noun: &str) -> Result<(usize, &'a str), Error> {
+        let start = *self;
+        let result = (|| {
+            let count = self.unsigned::<usize>()?;
+            self.tag(" ")?;
+            let name = self.until(&format!(" {}", noun))?;
+            self.tag(" ")?;
+            self.tag(noun)?;
+            self.eat("s");
+            Ok((count, name))
+        })();
+        if result.is_err() {
+            *self = start;
+        }
+        result
+    }
+
+    /// Succeeds only if the whole line has been consumed.
+    pub fn end(&self) -> Result<(), Error> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.line.error_at(self.pos, self.rest().len(), "end of line"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(text: &str) -> Cursor<'_> {
+        Cursor::new(Line::new(1, text))
+    }
+
+    #[test]
+    fn test_signed() {
+        assert_eq!(cursor("+12").signed::<isize>().unwrap(), 12);
+        assert_eq!(cursor("-7 x").signed::<isize>().unwrap(), -7);
+        assert_eq!(cursor("+3").signed::<usize>().unwrap(), 3);
+        let mut c = cursor("-x");
+        assert_eq!(c.signed::<isize>().unwrap_err().column, 1);
+        assert_eq!(c.position(), 0);
+        assert!(cursor("-3").signed::<usize>().is_err());
+    }
+
+    #[test]
+    fn test_key_value() {
+        let mut c = cursor("ecl:gry pid:860033327");
+        assert_eq!(c.key_value(':').unwrap(), ("ecl", "gry"));
+        c.whitespace();
+        assert_eq!(c.key_value(':').unwrap(), ("pid", "860033327"));
+        assert!(c.end().is_ok());
+        let error = cursor("ecl gry").key_value(':').unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (4, " "));
+    }
+
+    #[test]
+    fn test_quantities() {
+        let mut c = cursor("1 bright white bag, 2 muted yellow bags.");
+        let bags = c.delimited(", ", |c| c.quantity("bag")).unwrap();
+        assert_eq!(bags, vec![(1, "bright white"), (2, "muted yellow")]);
+        assert!(c.tag(".").is_ok());
+        assert!(c.end().is_ok());
+        let mut c = cursor("x bright white bag");
+        assert!(c.quantity("bag").is_err());
+        assert_eq!(c.position(), 0);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut c = cursor("1-3 a: abcde");
+        assert_eq!(c.unsigned::<usize>().unwrap(), 1);
+        let error = c.tag(" ").unwrap_err();
+        assert_eq!((error.column, error.found.as_str(), error.expected.as_str()), (2, "-", "` `"));
+        let error = c.end().unwrap_err();
+        assert_eq!(error.found, "-3 a: abcde");
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::str::FromStr;
+
+mod cursor;
+mod records;
+
+pub use cursor::Cursor;
+pub use records::{records, Record, Records};
+
+/// A parse failure pointing at the offending text of an input line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+    /// The full text of the offending line
+    pub text: String,
+    /// The offending token, empty when the line ended early
+    pub found: String,
+    /// Description of what was expected instead
+    pub expected: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: expected {}, ", self.line, self.column, self.expected)?;
+        if self.found.is_empty() {
+            writeln!(f, "found end of line")?;
+        } else {
+            writeln!(f, "found `{}`", self.found)?;
+        }
+        let gutter = self.line.to_string().len();
+        writeln!(f, "{:gutter$} |", "", gutter = gutter)?;
+        writeln!(f, "{} | {}", self.line, self.text)?;
+        write!(
+            f,
+            "{:gutter$} | {:pad$}{}",
+            "",
+            "",
+            "^".repeat(self.found.chars().count().max(1)),
+            gutter = gutter,
+            pad = self.column - 1
+        )
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A single numbered line of puzzle input.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    number: usize,
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn new(number: usize, text: &'a str) -> Self {
+        Self { number, text }
+    }
+
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Starts scanning this line from the beginning.
+    pub fn cursor(&self) -> Cursor<'a> {
+        Cursor::new(*self)
+    }
+
+    /// Byte offset of `token` within this line, which must be a slice of it.
+    pub fn offset_of(&self, token: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let offset = (token.as_ptr() as usize).wrapping_sub(start);
+        debug_assert!(offset <= self.text.len(), "token is not part of the line");
+        offset.min(self.text.len())
+    }
+
+    /// Builds an error pointing at `token`, which must be a slice of this line.
+    pub fn error(&self, token: &str, expected: impl Into<String>) -> Error {
+        let offset = self.offset_of(token);
+        self.error_at(offset, token.len(), expected)
+    }
+
+    /// Builds an error pointing at `len` bytes starting at byte `offset` of this line.
+    pub fn error_at(&self, offset: usize, len: usize, expected: impl Into<String>) -> Error {
+        let offset = offset.min(self.text.len());
+        let end = (offset + len).min(self.text.len());
+        Error {
+            line: self.number,
+            column: self.text[..offset].chars().count() + 1,
+            text: self.text.to_string(),
+            found: self.text[offset..end].to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Builds an error pointing just past the end of this line.
+    pub fn error_at_end(&self, expected: impl Into<String>) -> Error {
+        self.error_at(self.text.len(), 0, expected)
+    }
+
+    /// Parses `token`, which must be a slice of this line, reporting its position on failure.
+    pub fn parse<T: FromStr>(&self, token: &str, expected: &str) -> Result<T, Error> {
+        if token.is_empty() {
+            return Err(self.error(token, expected));
+        }
+        token.parse::<T>().map_err(|_| self.error(token, expected))
+    }
+}
+
+/// Iterator over numbered lines, with any trailing `\r` removed.
+#[derive(Debug, Clone)]
+pub struct Lines<'a> {
+    inner: std::iter::Enumerate<std::str::Lines<'a>>,
+    first: usize,
+}
+
+impl<'a> Lines<'a> {
+    /// Iterates over the lines of `input`, numbering them from `first`.
+    pub fn new(input: &'a str, first: usize) -> Self {
+        Self {
+            inner: input.lines().enumerate(),
+            first,
+        }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = Line<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(index, text)| Line::new(self.first + index, text.strip_suffix('\r').unwrap_or(text)))
+    }
+}
+
+/// Iterates over the lines of `input`, numbered from 1.
+pub fn lines(input: &str) -> Lines<'_> {
+    Lines::new(input, 1)
+}
+
+/// Parses every line of `input` as a `T`.
+pub fn parse_lines<T: FromStr>(input: &str, expected: &str) -> Result<Vec<T>, Error> {
+    lines(input).map(|line| line.parse(line.text(), expected)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(parse_lines::<isize>("1\n-2\n3", "integer").unwrap(), vec![1, -2, 3]);
+        let error = parse_lines::<isize>("1\nx2\n3", "integer").unwrap_err();
+        assert_eq!((error.line, error.column, error.found.as_str()), (2, 1, "x2"));
+    }
+
+    #[test]
+    fn test_column() {
+        let line = Line::new(4, "1-3 é: abcde");
+        let error = line.error(&line.text()[4..6], "letter a-z");
+        assert_eq!(error.column, 5);
+        assert_eq!(error.found, "é");
+        let error = line.error_at_end("more");
+        assert_eq!(error.column, 13);
+        assert_eq!(error.found, "");
+    }
+
+    #[test]
+    fn test_display() {
+        let line = Line::new(12, "acc +x1");
+        let error = line.parse::<isize>(&line.text()[4..], "signed integer").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 12, column 5: expected signed integer, found `+x1`\n   |\n12 | acc +x1\n   |     ^^^"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{Line, Lines};
+use std::iter::Peekable;
+
+/// A group of consecutive non-blank lines, borrowed from the input.
+#[derive(Debug, Clone, Copy)]
+pub struct Record<'a> {
+    number: usize,
+    text: &'a str,
+}
+
+impl<'a> Record<'a> {
+    /// Line number of the first line in the record.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// The text of the record, including the line breaks between its lines.
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn lines(&self) -> Lines<'a> {
+        Lines::new(self.text, self.number)
+    }
+}
+
+/// Iterator over records separated by one or more blank lines.
+///
+/// Lines holding only whitespace count as blank, and `\r\n` line endings are accepted,
+/// so leading, trailing or repeated blank lines never produce empty records.
+#[derive(Debug, Clone)]
+pub struct Records<'a> {
+    input: &'a str,
+    lines: Peekable<Lines<'a>>,
+}
+
+fn is_blank(line: &Line<'_>) -> bool {
+    line.text().trim().is_empty()
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Record<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.lines.next_if(is_blank).is_some() {}
+        let first = self.lines.next()?;
+        let mut last = first;
+        while let Some(line) = self.lines.next_if(|line| !is_blank(line)) {
+            last = line;
+        }
+        let start = first.text().as_ptr() as usize - self.input.as_ptr() as usize;
+        let end = last.text().as_ptr() as usize - self.input.as_ptr() as usize + last.text().len();
+        Some(Record {
+            number: first.number(),
+            text: &self.input[start..end],
+        })
+    }
+}
+
+/// Groups the lines of `input` into records separated by blank lines.
+pub fn records(input: &str) -> Records<'_> {
+    Records {
+        input,
+        lines: crate::lines(input).peekable(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn texts(input: &str) -> Vec<Vec<&str>> {
+        records(input).map(|r| r.lines().map(|l| l.text()).collect()).collect()
+    }
+
+    #[test]
+    fn test_records() {
+        assert_eq!(texts("a\nb\n\nc\n\n"), vec![vec!["a", "b"], vec!["c"]]);
+        assert_eq!(texts("\r\na\r\nb\r\n \r\n\r\nc\r\n"), vec![vec!["a", "b"], vec!["c"]]);
+        assert!(texts("\n\n  \n").is_empty());
+        let numbers = records("a\n\n\nb\nc").map(|r| r.number()).collect::<Vec<_>>();
+        assert_eq!(numbers, vec![1, 4]);
+    }
+
+    fn groups() -> impl Strategy<Value = Vec<Vec<String>>> {
+        prop::collection::vec(prop::collection::vec("[a-z:#0-9]{1,8}( [a-z:#0-9]{1,8}){0,3}", 1..5), 0..6)
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(groups in groups(), crlf in any::<bool>(), leading in 0..3usize, separators in 1..4usize, trailing in 0..3usize, padding in "[ \t]{0,2}") {
+            let newline = if crlf { "\r\n" } else { "\n" };
+            let blank = format!("{}{}", padding, newline);
+            let joined = groups
+                .iter()
+                .map(|group| group.join(newline))
+                .collect::<Vec<_>>()
+                .join(&format!("{}{}", newline, blank.repeat(separators)));
+            let input = format!("{}{}{}{}", blank.repeat(leading), joined, newline, blank.repeat(trailing));
+            let expected = groups.iter().map(|g| g.iter().map(String::as_str).collect::<Vec<_>>()).collect::<Vec<_>>();
+            prop_assert_eq!(texts(&input), expected);
+        }
+
+        #[test]
+        fn test_borrowed_and_numbered(input in "([a-z]{0,3}(\r?\n)){0,12}") {
+            let all = crate::lines(&input).collect::<Vec<_>>();
+            for record in records(&input) {
+                prop_assert!(!record.text().is_empty());
+                for line in record.lines() {
+                    prop_assert!(!line.text().trim().is_empty());
+                    prop_assert_eq!(line.text(), all[line.number() - 1].text());
+                    prop_assert_eq!(line.text().as_ptr(), all[line.number() - 1].text().as_ptr());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Runs `f` until at least `duration` has passed and returns its last result with the average time of
+/// a run. Repeating short runs keeps the clock resolution from dominating, and every result goes
+/// through [`black_box`] so repeated pure work is not optimized away.
+pub fn time<T, F: FnMut() -> T>(duration: Duration, mut f: F) -> (T, Duration) {
+    let start = Instant::now();
+    let mut result = black_box(f());
+    let mut runs = 1;
+    while start.elapsed() < duration {
+        result = black_box(f());
+        runs += 1;
+    }
+    (result, start.elapsed() / runs)
+}
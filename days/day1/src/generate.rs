@@ -0,0 +1,73 @@
+use rng::Rng;
+
+/// An expense report with exactly one set of `k` entries summing to the target.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub values: Vec<isize>,
+    /// Indices of the planted entries, ascending
+    pub planted: Vec<usize>,
+}
+
+fn smallest_prime_above(k: usize) -> isize {
+    (k as isize + 1..).find(|&n| (2..n).all(|d| n % d != 0)).expect("primes are unbounded")
+}
+
+/// Generates `size` positive entries where the only `k` entries summing to `target` are the planted ones.
+///
+/// Uniqueness comes from residues modulo a prime `m > k`: planted entries are all `a` and fillers all `a + 1`
+/// modulo `m`, with `k * a = target`. A set with `j` fillers is then off by `j` modulo `m`, which is non-zero
+/// for every `1 <= j <= k`.
+pub fn generate(size: usize, k: usize, target: isize, seed: u64) -> Report {
+    assert!(k >= 1 && size >= k, "need at least k entries");
+    let m = smallest_prime_above(k);
+    let a = (0..m).find(|a| (k as isize * a - target).rem_euclid(m) == 0).expect("k is invertible modulo m");
+    assert!(target >= k as isize * (a.max(1) + m), "target too small to plant {} entries", k);
+    let mut rng = Rng::new(seed);
+    let with_residue = |rng: &mut Rng, residue: isize, high: isize| {
+        let n = rng.range(if residue == 0 { 1 } else { 0 }, (high - residue) / m);
+        n * m + residue
+    };
+
+    let mut planted_values = (1..k).map(|_| with_residue(&mut rng, a, target / k as isize)).collect::<Vec<_>>();
+    planted_values.push(target - planted_values.iter().sum::<isize>());
+
+    let mut values = (0..size - k).map(|_| with_residue(&mut rng, (a + 1) % m, target)).collect::<Vec<_>>();
+    let mut planted = Vec::with_capacity(k);
+    for value in planted_values {
+        let index = rng.range(0, values.len() as isize) as usize;
+        for p in planted.iter_mut() {
+            if *p >= index {
+                *p += 1;
+            }
+        }
+        values.insert(index, value);
+        planted.push(index);
+    }
+    planted.sort_unstable();
+    Report { values, planted }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ksum::{count_k_sums, find_k_sum};
+
+    #[test]
+    fn test_planted_solution_is_unique() {
+        for k in 2..=4 {
+            for seed in 0..5 {
+                let report = generate(300, k, 2020, seed);
+                assert_eq!(report.values.len(), 300);
+                assert!(report.values.iter().all(|v| *v > 0));
+                assert_eq!(count_k_sums(&report.values, k, 2020).unwrap(), 1, "k {} seed {}", k, seed);
+                assert_eq!(find_k_sum(&report.values, k, 2020).unwrap().indices, report.planted);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reproducible() {
+        assert_eq!(generate(50, 3, 2020, 7).values, generate(50, 3, 2020, 7).values);
+        assert_ne!(generate(50, 3, 2020, 7).values, generate(50, 3, 2020, 8).values);
+    }
+}
@@ -0,0 +1,379 @@
+use crate::{Error, TableTooLarge};
+use snafu::ensure;
+use std::collections::HashMap;
+
+/// Entries of an expense report that add up to a target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KSum {
+    /// Indices into the report, ascending
+    pub indices: Vec<usize>,
+    /// The values at `indices`
+    pub values: Vec<isize>,
+}
+
+impl KSum {
+    pub fn new(values: &[isize], mut indices: Vec<usize>) -> Self {
+        indices.sort_unstable();
+        let values = indices.iter().map(|&index| values[index]).collect();
+        Self { indices, values }
+    }
+
+    pub fn product(&self) -> isize {
+        self.values.iter().product()
+    }
+
+    /// The product widened to `i128`, or `None` if even that overflows.
+    pub fn checked_product(&self) -> Option<i128> {
+        self.values.iter().try_fold(1i128, |acc, &value| acc.checked_mul(value as i128))
+    }
+}
+
+/// Iterator over the `k`-combinations of `0..n` in lexicographic order.
+pub struct Combinations {
+    indices: Vec<usize>,
+    n: usize,
+    done: bool,
+}
+
+impl Combinations {
+    pub fn new(n: usize, k: usize) -> Self {
+        Self {
+            indices: (0..k).collect(),
+            n,
+            done: k > n,
+        }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let current = self.indices.clone();
+        let k = self.indices.len();
+        match (0..k).rev().find(|&i| self.indices[i] != i + self.n - k) {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..k {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(current)
+    }
+}
+
+fn find_pair(values: &[isize], target: isize) -> Option<Vec<usize>> {
+    let mut seen = HashMap::new();
+    for (index, value) in values.iter().enumerate() {
+        // a partner outside the isize range cannot be in the report
+        if let Some(&other) = target.checked_sub(*value).and_then(|need| seen.get(&need)) {
+            return Some(vec![other, index]);
+        }
+        seen.entry(*value).or_insert(index);
+    }
+    None
+}
+
+fn find_triple(values: &[isize], target: isize) -> Option<Vec<usize>> {
+    let mut order = (0..values.len()).collect::<Vec<_>>();
+    order.sort_unstable_by_key(|&index| values[index]);
+    for first in 0..order.len() {
+        let mut low = first + 1;
+        let mut high = order.len().saturating_sub(1);
+        while low < high {
+            let sum = [first, low, high].iter().map(|&i| values[order[i]] as i128).sum::<i128>();
+            let target = target as i128;
+            if sum == target {
+                return Some(vec![order[first], order[low], order[high]]);
+            } else if sum < target {
+                low += 1;
+            } else {
+                high -= 1;
+            }
+        }
+    }
+    None
+}
+
+fn find_meet_in_the_middle(values: &[isize], k: usize, target: isize) -> Option<Vec<usize>> {
+    let half = k / 2;
+    // sums of up to k entries, in i128 so they cannot overflow
+    let sum = |combination: &[usize]| combination.iter().map(|&index| values[index] as i128).sum::<i128>();
+    let mut sums = HashMap::<i128, Vec<Vec<usize>>>::new();
+    for combination in Combinations::new(values.len(), half) {
+        sums.entry(sum(&combination)).or_default().push(combination);
+    }
+    for combination in Combinations::new(values.len(), k - half) {
+        if let Some(candidates) = sums.get(&(target as i128 - sum(&combination))) {
+            if let Some(other) = candidates.iter().find(|other| other.iter().all(|index| !combination.contains(index))) {
+                return Some(other.iter().chain(combination.iter()).copied().collect());
+            }
+        }
+    }
+    None
+}
+
+/// Finds `k` distinct entries of `values` summing to `target`.
+///
+/// Uses hashing for `k = 2`, sorting with two pointers for `k = 3` and meet-in-the-middle beyond that.
+pub fn find_k_sum(values: &[isize], k: usize, target: isize) -> Option<KSum> {
+    let indices = match k {
+        0 if target == 0 => Some(Vec::new()),
+        0 => None,
+        1 => values.iter().position(|&value| value == target).map(|index| vec![index]),
+        2 => find_pair(values, target),
+        3 => find_triple(values, target),
+        _ => find_meet_in_the_middle(values, k, target),
+    }?;
+    Some(KSum::new(values, indices))
+}
+
+/// Iterator over every distinct set of `k` entries summing to a target, see [`k_sums`].
+pub struct KSums<'a> {
+    values: &'a [isize],
+    /// Indices into `values`, ordered by value
+    sorted: Vec<usize>,
+    /// Prefix sums of the values in `sorted` order, in `i128` like all sums here so they cannot overflow
+    prefix: Vec<i128>,
+    k: usize,
+    target: i128,
+    /// Positions in `sorted` chosen so far, strictly increasing
+    path: Vec<usize>,
+    sum: i128,
+    started: bool,
+    done: bool,
+}
+
+impl<'a> KSums<'a> {
+    fn new(values: &'a [isize], k: usize, target: isize) -> Self {
+        let mut sorted = (0..values.len()).collect::<Vec<_>>();
+        sorted.sort_unstable_by_key(|&index| values[index]);
+        let mut prefix = vec![0];
+        for &index in &sorted {
+            prefix.push(prefix[prefix.len() - 1] + values[index] as i128);
+        }
+        Self {
+            values,
+            sorted,
+            prefix,
+            k,
+            target: target as i128,
+            path: Vec::with_capacity(k),
+            sum: 0,
+            started: false,
+            done: k > values.len(),
+        }
+    }
+
+    /// First position from `from` that can still be completed to the target.
+    fn candidate(&self, from: usize) -> Option<usize> {
+        let n = self.sorted.len();
+        let rest = self.k - self.path.len() - 1;
+        for position in from..n.saturating_sub(rest) {
+            let need = self.target - self.sum - self.values[self.sorted[position]] as i128;
+            let min = self.prefix[position + 1 + rest] - self.prefix[position + 1];
+            let max = self.prefix[n] - self.prefix[n - rest];
+            // values only grow along `sorted`, so once `need` is below the minimum it stays there
+            if need < min {
+                return None;
+            }
+            if need <= max {
+                return Some(position);
+            }
+        }
+        None
+    }
+
+    /// Drops the last choice, returning the position to continue from.
+    fn backtrack(&mut self) -> Option<usize> {
+        let position = self.path.pop()?;
+        self.sum -= self.values[self.sorted[position]] as i128;
+        Some(position + 1)
+    }
+}
+
+impl<'a> Iterator for KSums<'a> {
+    type Item = KSum;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut from = if self.started { self.backtrack() } else { Some(0) };
+        self.started = true;
+        while let Some(start) = from {
+            if self.path.len() == self.k {
+                if self.sum == self.target {
+                    let indices = self.path.iter().map(|&position| self.sorted[position]).collect();
+                    return Some(KSum::new(self.values, indices));
+                }
+                break;
+            }
+            from = match self.candidate(start) {
+                Some(position) => {
+                    self.path.push(position);
+                    self.sum += self.values[self.sorted[position]] as i128;
+                    Some(position + 1)
+                }
+                None => self.backtrack(),
+            };
+        }
+        self.done = true;
+        None
+    }
+}
+
+/// Iterates over every set of `k` distinct entries of `values` summing to `target`, each reported once.
+pub fn k_sums(values: &[isize], k: usize, target: isize) -> KSums<'_> {
+    KSums::new(values, k, target)
+}
+
+/// Largest dynamic programming table [`count_k_sums`] builds, in cells of 16 bytes.
+pub const MAX_CELLS: u128 = 1 << 24;
+
+/// Counts the sets of `k` distinct entries of `values` summing to `target` without enumerating them.
+///
+/// Values are shifted by the minimum so the dynamic programming table only spans `0..=target`,
+/// making this `O(n * k * target)` regardless of how many sets there are. The shift is done in
+/// `i128` so it cannot overflow, and a table of more than [`MAX_CELLS`] cells is an error.
+pub fn count_k_sums(values: &[isize], k: usize, target: isize) -> Result<u128, Error> {
+    let min = values.iter().copied().min().unwrap_or(0) as i128;
+    let max = values.iter().copied().max().unwrap_or(0) as i128;
+    let shifted_target = target as i128 - min * k as i128;
+    if k > values.len() || shifted_target < 0 || shifted_target > (max - min) * k as i128 {
+        return Ok(0);
+    }
+    let cells = (k as u128 + 1) * (shifted_target as u128 + 1);
+    ensure!(cells <= MAX_CELLS, TableTooLarge { cells, limit: MAX_CELLS });
+    let width = shifted_target as usize + 1;
+    // ways[j * width + s] is the number of sets of j entries with shifted sum s
+    let mut ways = vec![0u128; (k + 1) * width];
+    ways[0] = 1;
+    for value in values {
+        let value = (*value as i128 - min) as usize;
+        for j in (1..=k).rev() {
+            for sum in (value..width).rev() {
+                ways[j * width + sum] += ways[(j - 1) * width + sum - value];
+            }
+        }
+    }
+    Ok(ways[k * width + width - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(seed: u64, len: usize) -> Vec<isize> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                ((state >> 33) % 41) as isize - 20
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_combinations() {
+        let all = Combinations::new(4, 2).collect::<Vec<_>>();
+        assert_eq!(all, vec![vec![0, 1], vec![0, 2], vec![0, 3], vec![1, 2], vec![1, 3], vec![2, 3]]);
+        assert_eq!(Combinations::new(3, 0).count(), 1);
+        assert_eq!(Combinations::new(2, 3).count(), 0);
+    }
+
+    #[test]
+    fn test_find_k_sum() {
+        let values = [1721, 979, 366, 299, 675, 1456];
+        assert_eq!(find_k_sum(&values, 2, 2020).unwrap().indices, vec![0, 3]);
+        assert_eq!(find_k_sum(&values, 3, 2020).unwrap().values, vec![979, 366, 675]);
+        assert_eq!(find_k_sum(&values, 4, 1721 + 979 + 366 + 299).unwrap().product(), 1721 * 979 * 366 * 299);
+        assert_eq!(find_k_sum(&values, 2, 1010), None);
+        assert_eq!(find_k_sum(&[1010, 7], 2, 2020), None);
+        assert_eq!(find_k_sum(&values, 7, 0), None);
+        assert_eq!(find_k_sum(&[isize::MIN, 5], 2, 2020), None);
+        assert_eq!(find_k_sum(&[3, isize::MIN, isize::MAX], 2, -1).unwrap().indices, vec![1, 2]);
+        assert_eq!(
+            find_k_sum(&[isize::MAX, isize::MIN, 5, isize::MAX], 3, isize::MAX - 1).unwrap().indices,
+            vec![0, 1, 3]
+        );
+        assert_eq!(find_k_sum(&[isize::MAX, isize::MIN, 1], 3, 5), None);
+        assert_eq!(
+            find_k_sum(&[isize::MIN, isize::MAX, 1, isize::MIN, isize::MAX], 4, -2).unwrap().indices,
+            vec![0, 1, 3, 4]
+        );
+        assert_eq!(find_k_sum(&[isize::MIN, isize::MIN, 1, 2], 4, 0), None);
+    }
+
+    #[test]
+    fn test_k_sums() {
+        let values = [1, 2, 3, 4, 3];
+        let mut all = k_sums(&values, 2, 6).map(|sum| sum.indices).collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, vec![vec![1, 3], vec![2, 4]]);
+        assert_eq!(k_sums(&values, 0, 0).count(), 1);
+        assert_eq!(k_sums(&values, 0, 1).count(), 0);
+        assert_eq!(k_sums(&values, 6, 13).count(), 0);
+        assert_eq!(count_k_sums(&values, 2, 6).unwrap(), 2);
+        assert_eq!(count_k_sums(&[-5, 5, 0, 10], 2, 5).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_count_extremes() {
+        assert_eq!(count_k_sums(&[isize::MIN, isize::MAX], 2, -1).ok(), None);
+        assert_eq!(count_k_sums(&[isize::MIN, 0], 1, isize::MAX).unwrap(), 0);
+        assert!(matches!(count_k_sums(&[-10_000_000, 5, 7], 3, 0), Err(Error::TableTooLarge { .. })));
+        assert_eq!(count_k_sums(&[isize::MIN, isize::MIN + 1], 2, isize::MIN).unwrap(), 0);
+        assert_eq!(count_k_sums(&[isize::MAX, 1], 1, isize::MIN).unwrap(), 0);
+        assert_eq!(count_k_sums(&[-1_000, 1_000, 0], 2, 0).unwrap(), 1);
+        let extremes = [isize::MAX, isize::MIN, isize::MAX, isize::MIN, 1, -1];
+        let mut found = k_sums(&extremes, 2, -1).map(|sum| sum.indices).collect::<Vec<_>>();
+        found.sort();
+        assert_eq!(found, vec![vec![0, 1], vec![0, 3], vec![1, 2], vec![2, 3]]);
+        assert_eq!(k_sums(&extremes, 4, -2).count(), 1);
+        assert_eq!(k_sums(&extremes, 3, isize::MAX).count(), 2);
+        assert_eq!(k_sums(&extremes, 2, isize::MIN).count(), 0);
+    }
+
+    #[test]
+    fn test_enumeration_matches_brute_force() {
+        for seed in 0..20 {
+            let values = generate(seed, 10);
+            for k in 0..=5 {
+                for target in -15..=15 {
+                    let mut brute = Combinations::new(values.len(), k)
+                        .filter(|c| c.iter().map(|&i| values[i]).sum::<isize>() == target)
+                        .collect::<Vec<_>>();
+                    let mut found = k_sums(&values, k, target).map(|sum| sum.indices).collect::<Vec<_>>();
+                    brute.sort();
+                    found.sort();
+                    assert_eq!(found, brute, "seed {} k {} target {}", seed, k, target);
+                    assert_eq!(count_k_sums(&values, k, target).unwrap(), brute.len() as u128);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for seed in 0..40 {
+            let values = generate(seed, 9);
+            for k in 1..=5 {
+                for target in -12..=12 {
+                    let brute = Combinations::new(values.len(), k).any(|c| c.iter().map(|&i| values[i]).sum::<isize>() == target);
+                    let found = find_k_sum(&values, k, target);
+                    assert_eq!(found.is_some(), brute, "seed {} k {} target {}", seed, k, target);
+                    if let Some(found) = found {
+                        assert_eq!(found.values.iter().sum::<isize>(), target);
+                        assert!(found.indices.windows(2).all(|w| w[0] < w[1]));
+                    }
+                }
+            }
+        }
+    }
+}
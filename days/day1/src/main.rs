@@ -1,55 +1,73 @@
-use input::Input;
-use snafu::{ResultExt, Snafu};
-use snafu_cli_debug::SnafuCliDebug;
+use error::AnswerExt;
+use ksum::{count_k_sums, find_k_sum, k_sums};
+use subset::{find_subset_sum, Objective};
 
-#[derive(Snafu, SnafuCliDebug)]
-pub enum Error {
-    /// Error loading input
-    LoadingInput { source: input::Error },
-    /// Error converting string to integer
-    Parsing { source: std::num::ParseIntError },
+mod generate;
+mod ksum;
+mod scaling;
+mod subset;
+
+error::day_error! {
+    #[snafu(display("Dynamic programming table of {} cells is over the limit of {}", cells, limit))]
+    TableTooLarge { cells: u128, limit: u128 },
 }
 
 fn parse(input: &str) -> Result<Vec<isize>, Error> {
-    Ok(input.lines().map(|l| l.parse::<isize>().context(Parsing)).collect::<Result<Vec<_>, Error>>()?)
+    Ok(parse::parse_lines(input, "integer")?)
 }
 
-fn part1(input: &Vec<isize>) -> Result<Option<isize>, Error> {
-    for (index1, value1) in input.iter().enumerate() {
-        for (index2, value2) in input.iter().enumerate() {
-            if index1 != index2 {
-                if value1 + value2 == 2020 {
-                    return Ok(Some(value1 * value2));
-                }
-            }
-        }
-    }
-    Ok(None)
+fn part1(input: &[isize]) -> Result<Option<isize>, Error> {
+    Ok(find_k_sum(input, 2, 2020).map(|sum| sum.product()))
 }
 
-fn part2(input: &Vec<isize>) -> Result<Option<isize>, Error> {
-    for (index1, value1) in input.iter().enumerate() {
-        for (index2, value2) in input.iter().enumerate() {
-            for (index3, value3) in input.iter().enumerate() {
-                if index1 != index2 && index1 != index3 && index2 != index3 {
-                    if value1 + value2 + value3 == 2020 {
-                        return Ok(Some(value1 * value2 * value3));
-                    }
-                }
-            }
-        }
-    }
-    Ok(None)
+fn part2(input: &[isize]) -> Result<Option<isize>, Error> {
+    Ok(find_k_sum(input, 3, 2020).map(|sum| sum.product()))
 }
 
 fn main() -> Result<(), Error> {
-    let input = Input::open("config.toml").context(LoadingInput)?.get(1).context(LoadingInput)?;
+    if cli::has_flag("scaling") {
+        scaling::run();
+        return Ok(());
+    }
+    let input = error::load(1)?;
     let input = parse(&input)?;
-    println!("Part 1 {}", part1(&input)?.unwrap());
-    println!("Part 2 {}", part2(&input)?.unwrap());
+    println!("Part 1 {}", part1(&input)?.answer(1)?);
+    println!("Part 2 {}", part2(&input)?.answer(2)?);
+    for (part, k) in [(1, 2), (2, 3)].iter() {
+        let count = match count_k_sums(&input, *k, 2020) {
+            Ok(count) => count,
+            Err(error) => {
+                eprintln!("Part {} answer not checked for uniqueness: {}", part, error);
+                continue;
+            }
+        };
+        if count > 1 {
+            eprintln!("Part {} answer is not unique, {} sets of {} entries sum to 2020", part, count, k);
+            for sum in k_sums(&input, *k, 2020) {
+                eprintln!("  {:?} at {:?}", sum.values, sum.indices);
+            }
+        }
+    }
+    if cli::has_flag("subset") {
+        print_subsets(&input);
+    }
     Ok(())
 }
 
+/// Prints a subset of any size summing to 2020 for each objective.
+fn print_subsets(input: &[isize]) {
+    for objective in [Objective::Any, Objective::MaxProduct, Objective::MinProduct].iter() {
+        match find_subset_sum(input, 2020, *objective) {
+            Ok(Some(subset)) => match subset.checked_product() {
+                Some(product) => println!("Subset {:?}: {:?} product {}", objective, subset.values, product),
+                None => println!("Subset {:?}: {:?} product overflows", objective, subset.values),
+            },
+            Ok(None) => println!("Subset {:?}: none sums to 2020", objective),
+            Err(error) => println!("Subset {:?}: not searched, {}", objective, error),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
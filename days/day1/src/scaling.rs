@@ -0,0 +1,75 @@
+use crate::generate::{generate, Report};
+use crate::ksum::find_k_sum;
+use std::time::Duration;
+
+/// Stop growing the input once a single solve takes longer than this.
+const BUDGET: Duration = Duration::from_millis(500);
+const MAX_SIZE: usize = 1 << 16;
+
+/// Time to solve `report`, checking the solver finds the planted entries.
+fn time_solve(report: &Report, k: usize) -> Duration {
+    let (found, time) = timing::time(Duration::from_millis(20), || find_k_sum(&report.values, k, 2020).map(|sum| sum.indices));
+    assert_eq!(found.as_ref(), Some(&report.planted), "planted solution not found");
+    time
+}
+
+/// Least squares slope of `log(time)` against `log(size)`, i.e. the exponent in `time ~ size^e`.
+pub fn exponent(samples: &[(usize, Duration)]) -> Option<f64> {
+    let points = samples
+        .iter()
+        .filter(|(_, time)| *time > Duration::from_nanos(0))
+        .map(|(size, time)| ((*size as f64).ln(), time.as_secs_f64().ln()))
+        .collect::<Vec<_>>();
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+    let variance = points.iter().map(|(x, _)| (x - mean_x) * (x - mean_x)).sum::<f64>();
+    if variance == 0.0 {
+        None
+    } else {
+        Some(covariance / variance)
+    }
+}
+
+/// Runs the k-sum solver on generated reports of doubling size and prints the fitted complexity.
+pub fn run() {
+    for k in 2..=4 {
+        println!("k = {}", k);
+        println!("{:>8} {:>14}", "size", "time");
+        let mut samples = Vec::new();
+        let mut size = 64;
+        while size <= MAX_SIZE {
+            let report = generate(size, k, 2020, size as u64);
+            let time = time_solve(&report, k);
+            println!("{:>8} {:>14?}", size, time);
+            samples.push((size, time));
+            if time > BUDGET {
+                break;
+            }
+            size *= 2;
+        }
+        // the smallest sizes are dominated by constant overhead, so fit the upper half
+        match exponent(&samples[samples.len() / 2..]) {
+            Some(e) => println!("empirical complexity O(n^{:.2})\n", e),
+            None => println!("not enough samples to fit\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponent() {
+        let quadratic = (1..6).map(|i| (i * 100, Duration::from_micros((i * i * 50) as u64))).collect::<Vec<_>>();
+        assert!((exponent(&quadratic).unwrap() - 2.0).abs() < 1e-6);
+        let linear = [(100, Duration::from_millis(1)), (400, Duration::from_millis(4))];
+        assert!((exponent(&linear).unwrap() - 1.0).abs() < 1e-6);
+        assert_eq!(exponent(&linear[..1]), None);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::ksum::KSum;
+use crate::{Error, TableTooLarge};
+use snafu::ensure;
+use std::cmp::Ordering;
+
+/// How to choose among the subsets that reach the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    Any,
+    MaxProduct,
+    MinProduct,
+}
+
+/// Sign and natural log of the absolute value of a product, so products of many entries can be
+/// compared without overflowing.
+#[derive(Debug, Clone, Copy)]
+struct Product {
+    sign: isize,
+    log: f64,
+}
+
+impl Product {
+    fn one() -> Self {
+        Self { sign: 1, log: 0.0 }
+    }
+
+    fn times(self, value: isize) -> Self {
+        if value == 0 || self.sign == 0 {
+            Self { sign: 0, log: 0.0 }
+        } else {
+            Self {
+                sign: self.sign * value.signum(),
+                log: self.log + (value as f64).abs().ln(),
+            }
+        }
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        let magnitude = self.log.partial_cmp(&other.log).unwrap_or(Ordering::Equal);
+        match (self.sign.cmp(&other.sign), self.sign) {
+            (Ordering::Equal, 1) => magnitude,
+            (Ordering::Equal, -1) => magnitude.reverse(),
+            (ordering, _) => ordering,
+        }
+    }
+}
+
+/// Where a table entry came from in the previous row.
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    /// Entry skipped, continue from the given slot of the same sum
+    Skip(usize),
+    /// Entry taken on top of the given slot of the reduced sum
+    Take(usize),
+    /// Entry taken as the first of the subset
+    First,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    product: Product,
+    source: Source,
+}
+
+const MAX: usize = 0;
+const MIN: usize = 1;
+
+/// Largest dynamic programming table [`find_subset_sum`] builds, in cells of one per entry and sum.
+pub const MAX_CELLS: u128 = 1 << 22;
+
+/// Range of sums worth tracking: everything reachable, clipped at the target when the sign of the
+/// entries means a sum can never come back.
+#[derive(Debug, Clone, Copy)]
+struct Sums {
+    low: isize,
+    width: usize,
+}
+
+impl Sums {
+    /// The range for `values`, `None` when the target is out of reach, or an error when the table
+    /// would have more than [`MAX_CELLS`] cells. The bounds are summed in `i128` so they cannot overflow.
+    fn new(values: &[isize], target: isize) -> Result<Option<Self>, Error> {
+        let mut low: i128 = values.iter().filter(|v| **v < 0).map(|v| *v as i128).sum();
+        let mut high: i128 = values.iter().filter(|v| **v > 0).map(|v| *v as i128).sum();
+        let target = target as i128;
+        if low == 0 {
+            high = high.min(target);
+        }
+        if high == 0 {
+            low = low.max(target);
+        }
+        if target < low || target > high {
+            return Ok(None);
+        }
+        let width = (high - low) as u128 + 1;
+        let cells = width * values.len() as u128;
+        ensure!(cells <= MAX_CELLS, TableTooLarge { cells, limit: MAX_CELLS });
+        // the range includes 0 or the target, so with this few cells both ends fit in an isize
+        Ok(Some(Self {
+            low: low as isize,
+            width: width as usize,
+        }))
+    }
+
+    /// Table column holding `sum`, if it is in range.
+    fn slot(&self, sum: isize) -> Option<usize> {
+        let offset = sum - self.low;
+        if offset >= 0 && (offset as usize) < self.width {
+            Some(offset as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Table column holding `sum - value`, if it is in range.
+    fn slot_before(&self, sum: isize, value: isize) -> Option<usize> {
+        sum.checked_sub(value).and_then(|sum| self.slot(sum))
+    }
+
+    fn sum(&self, slot: usize) -> isize {
+        self.low + slot as isize
+    }
+}
+
+fn find_any(values: &[isize], target: isize, sums: Sums) -> Option<Vec<usize>> {
+    // reachable[i][s] holds when a non-empty subset of the first i + 1 entries sums to sums.sum(s)
+    let mut reachable = vec![vec![false; sums.width]; values.len()];
+    for (i, &value) in values.iter().enumerate() {
+        for s in 0..sums.width {
+            let sum = sums.sum(s);
+            reachable[i][s] = sum == value || (i > 0 && (reachable[i - 1][s] || sums.slot_before(sum, value).is_some_and(|prev| reachable[i - 1][prev])));
+        }
+    }
+    let mut s = sums.slot(target).filter(|&s| reachable.last().is_some_and(|row| row[s]))?;
+    let mut indices = Vec::new();
+    for i in (0..values.len()).rev() {
+        if i > 0 && reachable[i - 1][s] {
+            continue;
+        }
+        indices.push(i);
+        let sum = sums.sum(s);
+        if sum == values[i] {
+            return Some(indices);
+        }
+        s = sums.slot_before(sum, values[i])?;
+    }
+    None
+}
+
+fn better(objective: usize, candidate: &Step, current: &Option<Step>) -> bool {
+    match current {
+        None => true,
+        Some(current) => match objective {
+            MAX => candidate.product.cmp(&current.product) == Ordering::Greater,
+            _ => candidate.product.cmp(&current.product) == Ordering::Less,
+        },
+    }
+}
+
+fn find_product(values: &[isize], target: isize, sums: Sums, objective: usize) -> Option<Vec<usize>> {
+    // rows[i][s] holds the largest and smallest products of non-empty subsets of the first i + 1
+    // entries summing to sums.sum(s)
+    let mut rows: Vec<Vec<[Option<Step>; 2]>> = Vec::with_capacity(values.len());
+    for (i, &value) in values.iter().enumerate() {
+        let mut row = vec![[None, None]; sums.width];
+        for (s, cell) in row.iter_mut().enumerate() {
+            let sum = sums.sum(s);
+            // at most the first entry plus skipping or taking from either previous cell
+            let mut candidates = [None; 5];
+            let mut count = 0;
+            let mut push = |step: Step| {
+                candidates[count] = Some(step);
+                count += 1;
+            };
+            if sum == value {
+                push(Step {
+                    product: Product::one().times(value),
+                    source: Source::First,
+                });
+            }
+            if i > 0 {
+                let prev = &rows[i - 1];
+                for from in [MAX, MIN].iter() {
+                    if let Some(step) = prev[s][*from] {
+                        push(Step {
+                            product: step.product,
+                            source: Source::Skip(*from),
+                        });
+                    }
+                    if let Some(step) = sums.slot_before(sum, value).and_then(|p| prev[p][*from]) {
+                        push(Step {
+                            product: step.product.times(value),
+                            source: Source::Take(*from),
+                        });
+                    }
+                }
+            }
+            for candidate in candidates[..count].iter().flatten() {
+                for objective in [MAX, MIN].iter() {
+                    if better(*objective, candidate, &cell[*objective]) {
+                        cell[*objective] = Some(*candidate);
+                    }
+                }
+            }
+        }
+        rows.push(row);
+    }
+
+    let mut s = sums.slot(target)?;
+    let mut current = objective;
+    let mut indices = Vec::new();
+    for i in (0..values.len()).rev() {
+        let step = rows[i][s][current]?;
+        match step.source {
+            Source::Skip(from) => current = from,
+            Source::Take(from) => {
+                indices.push(i);
+                s = sums.slot_before(sums.sum(s), values[i])?;
+                current = from;
+            }
+            Source::First => {
+                indices.push(i);
+                return Some(indices);
+            }
+        }
+    }
+    None
+}
+
+/// Finds a non-empty subset of `values`, of any size, summing to `target`.
+///
+/// The dynamic programming table spans every reachable sum, clipped at `target` when all entries share
+/// a sign, so the cost is `O(n * range)`. Products are compared by sign and logarithm, which keeps large
+/// subsets comparable but can pick either of two products within floating point error of each other.
+/// A table of more than [`MAX_CELLS`] cells is an error rather than an allocation of that size.
+pub fn find_subset_sum(values: &[isize], target: isize, objective: Objective) -> Result<Option<KSum>, Error> {
+    let sums = match Sums::new(values, target)? {
+        Some(sums) => sums,
+        None => return Ok(None),
+    };
+    let indices = match objective {
+        Objective::Any => find_any(values, target, sums),
+        Objective::MaxProduct => find_product(values, target, sums, MAX),
+        Objective::MinProduct => find_product(values, target, sums, MIN),
+    };
+    Ok(indices.map(|indices| KSum::new(values, indices)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ksum::Combinations;
+
+    fn brute(values: &[isize], target: isize) -> Vec<i128> {
+        (1..=values.len())
+            .flat_map(|k| Combinations::new(values.len(), k))
+            .filter(|c| c.iter().map(|&i| values[i]).sum::<isize>() == target)
+            .map(|c| c.iter().map(|&i| values[i] as i128).product())
+            .collect()
+    }
+
+    #[test]
+    fn test_example() {
+        let values = [1721, 979, 366, 299, 675, 1456];
+        let any = find_subset_sum(&values, 2020, Objective::Any).unwrap().unwrap();
+        assert_eq!(any.values.iter().sum::<isize>(), 2020);
+        assert_eq!(find_subset_sum(&values, 2020, Objective::MaxProduct).unwrap().unwrap().product(), 241861950);
+        assert_eq!(find_subset_sum(&values, 2020, Objective::MinProduct).unwrap().unwrap().product(), 514579);
+        assert_eq!(find_subset_sum(&values, 1, Objective::Any).unwrap(), None);
+        assert_eq!(find_subset_sum(&values, 10000, Objective::MaxProduct).unwrap(), None);
+    }
+
+    #[test]
+    fn test_extremes() {
+        let huge = [isize::MIN, isize::MAX, 1, -1];
+        assert!(matches!(find_subset_sum(&huge, 0, Objective::Any), Err(Error::TableTooLarge { .. })));
+        assert!(matches!(
+            find_subset_sum(&[-3_000_000, 3_000_000], 0, Objective::Any),
+            Err(Error::TableTooLarge { .. })
+        ));
+        let negative = [isize::MIN, -1, -2];
+        assert_eq!(find_subset_sum(&negative, -3, Objective::MaxProduct).unwrap().unwrap().values, vec![-1, -2]);
+        assert_eq!(find_subset_sum(&negative, 1, Objective::Any).unwrap(), None);
+        let positive = [isize::MAX, 2, 3];
+        assert_eq!(find_subset_sum(&positive, 5, Objective::MinProduct).unwrap().unwrap().values, vec![2, 3]);
+        assert!(matches!(
+            find_subset_sum(&positive, isize::MAX, Objective::Any),
+            Err(Error::TableTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let sets: [&[isize]; 4] = [&[3, -2, 5, 4, -1, 0, 2], &[-4, -3, -2, 6, 1], &[2, 2, 3, 7, 1, 5], &[-1, -2, -3, -4]];
+        for values in sets.iter() {
+            for target in -10..=15 {
+                let products = brute(values, target);
+                let any = find_subset_sum(values, target, Objective::Any).unwrap();
+                let max = find_subset_sum(values, target, Objective::MaxProduct).unwrap();
+                let min = find_subset_sum(values, target, Objective::MinProduct).unwrap();
+                assert_eq!(any.is_some(), !products.is_empty(), "{:?} {}", values, target);
+                if let Some(any) = any {
+                    assert_eq!(any.values.iter().sum::<isize>(), target);
+                    let max = max.unwrap();
+                    let min = min.unwrap();
+                    assert_eq!(max.values.iter().sum::<isize>(), target);
+                    assert_eq!(min.values.iter().sum::<isize>(), target);
+                    assert_eq!(max.checked_product(), products.iter().max().copied(), "{:?} {}", values, target);
+                    assert_eq!(min.checked_product(), products.iter().min().copied(), "{:?} {}", values, target);
+                }
+            }
+        }
+    }
+}
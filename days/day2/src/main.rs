@@ -1,83 +1,174 @@
-use input::Input;
-use regex::Regex;
-use snafu::{OptionExt, ResultExt, Snafu};
-use snafu_cli_debug::SnafuCliDebug;
+use parse::Line;
+use policy::{CountInRange, Evaluator, Positions};
+use report::Format;
+use segmentation::{Letters, Segmentation};
 use std::ops::RangeInclusive;
 
-#[derive(Snafu, SnafuCliDebug)]
-pub enum Error {
-    /// Error loading input
-    LoadingInput { source: input::Error },
-    /// Error during regex operation
-    RegexOp { source: regex::Error },
-    /// Failed to parse input
-    Parsing { source: std::num::ParseIntError },
-    /// Missing single letter on password
-    Letter,
-    /// Regex didn't match
-    RegexMatch,
+mod policy;
+mod report;
+mod segmentation;
+mod stream;
+
+error::day_error! {
+    #[snafu(display("Unknown password policy `{}`", spec))]
+    UnknownPolicy { spec: String },
+    #[snafu(display("Invalid regex in password policy `{}`", spec))]
+    CompilingRegex { source: regex::Error, spec: String },
+    #[snafu(display("Unknown report format `{}`, expected table, csv or json", format))]
+    UnknownFormat { format: String },
+    /// Error reading input
+    Reading { source: std::io::Error },
 }
 
 #[derive(Debug)]
-struct Password {
+struct Password<'a> {
+    line: usize,
     frequency: RangeInclusive<usize>,
-    letter: char,
-    password: String,
+    letter: &'a str,
+    password: &'a str,
+    segmentation: Segmentation,
 }
 
-fn part1(input: &Vec<Password>) -> usize {
-    input
-        .iter()
-        .filter_map(|pass| {
-            let count = pass
-                .password
-                .chars()
-                .filter_map(|char| if char == pass.letter { Some(true) } else { None })
-                .count();
-            if pass.frequency.contains(&count) {
-                Some(true)
-            } else {
-                None
-            }
-        })
-        .count()
+impl<'a> Password<'a> {
+    fn letters(&self) -> Letters<'a> {
+        self.segmentation.letters(self.password)
+    }
+
+    /// Occurrences of the entry's letter in the password.
+    fn count(&self) -> usize {
+        self.letters().filter(|letter| *letter == self.letter).count()
+    }
+
+    /// The first bound past the end of the password, which makes the entry fail the position policy.
+    fn outside(&self) -> Option<usize> {
+        let length = self.letters().count();
+        [*self.frequency.start(), *self.frequency.end()]
+            .iter()
+            .copied()
+            .find(|position| *position > length)
+    }
 }
 
-fn part2(input: &Vec<Password>) -> usize {
-    input
-        .iter()
-        .filter_map(|pass| {
-            let start = pass.password.chars().nth(*pass.frequency.start() - 1).unwrap();
-            let end = pass.password.chars().nth(*pass.frequency.end() - 1).unwrap();
-            if (start == pass.letter || end == pass.letter) && start != end {
-                Some(true)
-            } else {
-                None
-            }
-        })
-        .count()
+fn part1(input: &[Password]) -> usize {
+    Evaluator::new(vec![Box::new(CountInRange)]).count_valid(input)
 }
 
-fn parse(input: &str) -> Result<Vec<Password>, Error> {
-    let regex = Regex::new(r#"^(\d+)-(\d+) ([a-z]): ([a-z]+)"#).context(RegexOp)?;
-    input
-        .lines()
-        .map(|l| {
-            let captures = regex.captures(l).context(RegexMatch)?;
-            Ok(Password {
-                frequency: captures[1].parse::<usize>().context(Parsing)?..=captures[2].parse::<usize>().context(Parsing)?,
-                letter: captures[3].chars().nth(0).context(Letter)?,
-                password: (&captures[4]).to_string(),
-            })
-        })
-        .collect::<Result<Vec<_>, Error>>()
+fn part2(input: &[Password]) -> usize {
+    Evaluator::new(vec![Box::new(Positions { min: 1, max: 1 })]).count_valid(input)
+}
+
+/// Parses one `start-end letter: password` entry, borrowing the letter and password from `line`.
+fn parse_line(line: Line<'_>, segmentation: Segmentation) -> Result<Password<'_>, parse::Error> {
+    let mut cursor = line.cursor();
+    let rest = cursor.rest();
+    let start = cursor.unsigned::<usize>()?;
+    let start_token = &rest[..rest.len() - cursor.rest().len()];
+    cursor.tag("-")?;
+    let rest = cursor.rest();
+    let end = cursor.unsigned::<usize>()?;
+    let end_token = &rest[..rest.len() - cursor.rest().len()];
+    cursor.tag(" ")?;
+    let letter = cursor.until(": ")?;
+    if segmentation.letters(letter).count() != 1 {
+        return Err(line.error(letter, "a single letter"));
+    }
+    cursor.tag(": ")?;
+    let password = cursor.take_while1(|_| true, "password")?;
+    // bounds past the end of the password are left to the policies, which count them as failures
+    for (position, token) in [(start, start_token), (end, end_token)].iter() {
+        if *position == 0 {
+            return Err(line.error(token, "1-based position"));
+        }
+    }
+    if start > end {
+        return Err(line.error(end_token, format!("bound of at least {}", start)));
+    }
+    Ok(Password {
+        line: line.number(),
+        frequency: start..=end,
+        letter,
+        password,
+        segmentation,
+    })
+}
+
+fn parse(input: &str, segmentation: Segmentation) -> Result<Vec<Password<'_>>, Error> {
+    Ok(parse::lines(input)
+        .map(|line| parse_line(line, segmentation))
+        .collect::<Result<Vec<_>, parse::Error>>()?)
+}
+
+/// The policies selected with `--policy`, or both puzzle interpretations when there are none.
+fn evaluator(specs: &[String]) -> Result<Evaluator, Error> {
+    if specs.is_empty() {
+        Ok(Evaluator::new(vec![Box::new(CountInRange), Box::new(Positions { min: 1, max: 1 })]))
+    } else {
+        Ok(Evaluator::new(specs.iter().map(|spec| policy::from_spec(spec)).collect::<Result<Vec<_>, _>>()?))
+    }
+}
+
+/// Validates the input as it is read, for password databases too large to load whole.
+fn run_streaming(specs: &[String], segmentation: Segmentation) -> Result<(), Error> {
+    let evaluator = evaluator(specs)?;
+    let reader = error::open(2)?;
+    #[cfg(feature = "parallel")]
+    let tally = if cli::has_flag("parallel") {
+        stream::validate_parallel(reader, &evaluator, segmentation)?
+    } else {
+        stream::validate(reader, &evaluator, segmentation)?
+    };
+    #[cfg(not(feature = "parallel"))]
+    let tally = stream::validate(reader, &evaluator, segmentation)?;
+    if specs.is_empty() {
+        println!("Part 1 {}", tally.valid[0]);
+        println!("Part 2 {}", tally.valid[1]);
+    } else {
+        let names = evaluator.policies().iter().map(|policy| policy.name()).collect::<Vec<_>>();
+        println!("Valid under {}: {} of {}", names.join(", "), tally.all, tally.entries);
+    }
+    Ok(())
 }
 
 fn main() -> Result<(), Error> {
-    let input = Input::open("config.toml").context(LoadingInput)?.get(2).context(LoadingInput)?;
-    let input = parse(&input)?;
-    println!("Part 1 {}", part1(&input));
-    println!("Part 2 {}", part2(&input));
+    let segmentation = if cli::has_flag("graphemes") {
+        Segmentation::Graphemes
+    } else {
+        Segmentation::Chars
+    };
+    let specs = cli::flags("policy");
+    if cli::has_flag("stream") {
+        return run_streaming(&specs, segmentation);
+    }
+
+    let input = error::load(2)?;
+    let input = parse(&input, segmentation)?;
+    let format = cli::flags("report").last().map(|format| format.parse::<Format>()).transpose()?;
+    if specs.is_empty() && format.is_none() {
+        println!("Part 1 {}", part1(&input));
+        for password in &input {
+            if let Some(position) = password.outside() {
+                eprintln!(
+                    "line {}: position {} is outside password `{}`, counted as invalid for part 2",
+                    password.line, position, password.password
+                );
+            }
+        }
+        println!("Part 2 {}", part2(&input));
+        return Ok(());
+    }
+
+    let evaluator = evaluator(&specs)?;
+    if let Some(format) = format {
+        print!("{}", report::render(format, &evaluator, &input));
+        return Ok(());
+    }
+    let names = evaluator.policies().iter().map(|policy| policy.name()).collect::<Vec<_>>();
+    for password in &input {
+        for failure in evaluator.evaluate(password) {
+            println!("line {}: {} failed {}: {}", password.line, password.password, failure.rule, failure.reason);
+        }
+    }
+    println!("Valid under {}: {}", names.join(", "), evaluator.count_valid(&input));
     Ok(())
 }
 
@@ -90,7 +181,7 @@ mod tests {
         let test_input = r#"1-3 a: abcde
 1-3 b: cdefg
 2-9 c: ccccccccc"#;
-        let input = parse(test_input).expect("parse");
+        let input = parse(test_input, Segmentation::Chars).expect("parse");
         assert_eq!(part1(&input), 2);
     }
 
@@ -99,7 +190,37 @@ mod tests {
         let test_input = r#"1-3 a: abcde
 1-3 b: cdefg
 2-9 c: ccccccccc"#;
-        let input = parse(test_input).expect("parse");
+        let input = parse(test_input, Segmentation::Chars).expect("parse");
         assert_eq!(part2(&input), 1);
     }
+
+    #[test]
+    fn test_parse_error() {
+        for (test_input, column) in &[
+            ("1-3 a: abcde\n1-3 b cdefg", 5),
+            ("1-3 a: abcde\n0-3 b: cdefg", 1),
+            ("1-3 a: abcde\n3-1 b: cdefg", 3),
+        ] {
+            match parse(test_input, Segmentation::Chars) {
+                Err(Error::Solver {
+                    source: error::Error::Parsing { source },
+                }) => assert_eq!((source.line, source.column), (2, *column)),
+                _ => panic!("expected parse error"),
+            }
+        }
+        let input = parse("1-9 b: cdefg\n1-9 b: bbbbb\n2-9 b: ab\n1-2 b: bb", Segmentation::Chars).expect("parse");
+        let outside = input.iter().map(Password::outside).collect::<Vec<_>>();
+        assert_eq!(outside, vec![Some(9), Some(9), Some(9), None]);
+        assert_eq!(part1(&input), 2);
+        assert_eq!(part2(&input), 0);
+    }
+
+    #[test]
+    fn test_unicode() {
+        let test_input = "1-3 é: éb\u{301}é\n1-2 日: 日本\n1-3 e\u{301}: ae\u{301}e\u{301}";
+        let input = parse(test_input, Segmentation::Graphemes).expect("parse");
+        assert_eq!(part1(&input), 3);
+        assert_eq!(part2(&input), 2);
+        assert!(parse(test_input, Segmentation::Chars).is_err());
+    }
 }
@@ -0,0 +1,325 @@
+use crate::{CompilingRegex, Error, Password, UnknownPolicy};
+use regex::Regex;
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A rule a password has to satisfy.
+pub trait Policy: Send + Sync {
+    /// Name used when selecting the policy and reporting failures.
+    fn name(&self) -> String;
+
+    /// Checks `password`, returning why it was rejected on failure.
+    fn check(&self, password: &Password) -> Result<(), String>;
+
+    /// Whether `password` passes, without building the reason for a failure.
+    fn accepts(&self, password: &Password) -> bool {
+        self.check(password).is_ok()
+    }
+}
+
+/// The letter has to occur a number of times within the entry's range (part 1).
+pub struct CountInRange;
+
+impl Policy for CountInRange {
+    fn name(&self) -> String {
+        "count".to_string()
+    }
+
+    fn check(&self, password: &Password) -> Result<(), String> {
+        let count = password.count();
+        if password.frequency.contains(&count) {
+            Ok(())
+        } else {
+            Err(format!(
+                "`{}` occurs {} times, expected {} to {}",
+                password.letter,
+                count,
+                password.frequency.start(),
+                password.frequency.end()
+            ))
+        }
+    }
+
+    fn accepts(&self, password: &Password) -> bool {
+        password.frequency.contains(&password.count())
+    }
+}
+
+/// The letter has to be at between `min` and `max` of the entry's two 1-based positions.
+///
+/// Part 2 is `Positions { min: 1, max: 1 }`.
+pub struct Positions {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Policy for Positions {
+    fn name(&self) -> String {
+        match (self.min, self.max) {
+            (1, 1) => "positions".to_string(),
+            (0, max) => format!("at-most:{}", max),
+            (min, max) => format!("positions:{}-{}", min, max),
+        }
+    }
+
+    fn check(&self, password: &Password) -> Result<(), String> {
+        let hits = Self::hits(password).map_err(|position| format!("position {} is outside the password", position))?;
+        if hits >= self.min && hits <= self.max {
+            Ok(())
+        } else {
+            Err(format!(
+                "`{}` is at {} of the positions, expected {} to {}",
+                password.letter, hits, self.min, self.max
+            ))
+        }
+    }
+
+    fn accepts(&self, password: &Password) -> bool {
+        Self::hits(password).is_ok_and(|hits| hits >= self.min && hits <= self.max)
+    }
+}
+
+impl Positions {
+    /// How many of the two positions hold the letter, or the first position outside the password.
+    fn hits(password: &Password) -> Result<usize, usize> {
+        let mut hits = 0;
+        for position in [*password.frequency.start(), *password.frequency.end()].iter() {
+            match position.checked_sub(1).and_then(|index| password.letters().nth(index)) {
+                Some(letter) if letter == password.letter => hits += 1,
+                Some(_) => {}
+                None => return Err(*position),
+            }
+        }
+        Ok(hits)
+    }
+}
+
+/// None of the letters may occur in the password.
+pub struct Forbidden {
+    pub letters: Vec<String>,
+}
+
+impl Policy for Forbidden {
+    fn name(&self) -> String {
+        format!("forbid:{}", self.letters.concat())
+    }
+
+    fn check(&self, password: &Password) -> Result<(), String> {
+        match self.find(password) {
+            Some(letter) => Err(format!("contains forbidden `{}`", letter)),
+            None => Ok(()),
+        }
+    }
+
+    fn accepts(&self, password: &Password) -> bool {
+        self.find(password).is_none()
+    }
+}
+
+impl Forbidden {
+    fn find<'a>(&self, password: &Password<'a>) -> Option<&'a str> {
+        password.letters().find(|letter| self.letters.iter().any(|forbidden| forbidden == letter))
+    }
+}
+
+/// The password has to match a regular expression.
+pub struct Matches {
+    pub pattern: Regex,
+}
+
+impl Policy for Matches {
+    fn name(&self) -> String {
+        format!("regex:{}", self.pattern)
+    }
+
+    fn check(&self, password: &Password) -> Result<(), String> {
+        if self.pattern.is_match(password.password) {
+            Ok(())
+        } else {
+            Err(format!("does not match `{}`", self.pattern))
+        }
+    }
+
+    fn accepts(&self, password: &Password) -> bool {
+        self.pattern.is_match(password.password)
+    }
+}
+
+/// Every letter has to occur at least the given number of times.
+pub struct Required {
+    pub letters: BTreeMap<String, usize>,
+}
+
+impl Policy for Required {
+    fn name(&self) -> String {
+        let letters = self.letters.iter().map(|(letter, n)| letter.repeat(*n)).collect::<String>();
+        format!("require:{}", letters)
+    }
+
+    fn check(&self, password: &Password) -> Result<(), String> {
+        for (letter, required) in &self.letters {
+            let count = password.letters().filter(|l| l == letter).count();
+            if count < *required {
+                return Err(format!("`{}` occurs {} times, expected at least {}", letter, count, required));
+            }
+        }
+        Ok(())
+    }
+
+    fn accepts(&self, password: &Password) -> bool {
+        self.letters
+            .iter()
+            .all(|(letter, required)| password.letters().filter(|l| l == letter).count() >= *required)
+    }
+}
+
+/// Builds a policy from a command line spec such as `count`, `at-most:1`, `forbid:xyz`, `regex:^[a-z]+$`
+/// or `require:aab`, where repeating a required letter raises its count. Letters in a spec are grapheme
+/// clusters, which match char by char counting whenever the letters are single chars.
+pub fn from_spec(spec: &str) -> Result<Box<dyn Policy>, Error> {
+    let (kind, argument) = match spec.find(':') {
+        Some(colon) => (&spec[..colon], Some(&spec[colon + 1..])),
+        None => (spec, None),
+    };
+    let policy: Box<dyn Policy> = match (kind, argument) {
+        ("count", None) => Box::new(CountInRange),
+        ("positions", None) => Box::new(Positions { min: 1, max: 1 }),
+        ("at-most", Some(max)) => match max.parse::<usize>() {
+            Ok(max) => Box::new(Positions { min: 0, max }),
+            Err(_) => return UnknownPolicy { spec }.fail(),
+        },
+        ("forbid", Some(letters)) if !letters.is_empty() => Box::new(Forbidden {
+            letters: letters.graphemes(true).map(str::to_string).collect(),
+        }),
+        ("regex", Some(pattern)) => Box::new(Matches {
+            pattern: Regex::new(pattern).context(CompilingRegex { spec })?,
+        }),
+        ("require", Some(letters)) if !letters.is_empty() => {
+            let mut counts = BTreeMap::new();
+            for letter in letters.graphemes(true) {
+                *counts.entry(letter.to_string()).or_insert(0) += 1;
+            }
+            Box::new(Required { letters: counts })
+        }
+        _ => return UnknownPolicy { spec }.fail(),
+    };
+    Ok(policy)
+}
+
+/// A policy that rejected a password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub rule: String,
+    pub reason: String,
+}
+
+/// Checks passwords against every one of a set of policies.
+pub struct Evaluator {
+    policies: Vec<Box<dyn Policy>>,
+}
+
+impl Evaluator {
+    pub fn new(policies: Vec<Box<dyn Policy>>) -> Self {
+        Self { policies }
+    }
+
+    pub fn policies(&self) -> &[Box<dyn Policy>] {
+        &self.policies
+    }
+
+    /// Every policy `password` fails, in the order the policies were given.
+    pub fn evaluate(&self, password: &Password) -> Vec<Failure> {
+        self.policies
+            .iter()
+            .filter_map(|policy| policy.check(password).err().map(|reason| Failure { rule: policy.name(), reason }))
+            .collect()
+    }
+
+    /// Whether `password` passes every policy, without building failure reasons.
+    pub fn is_valid(&self, password: &Password) -> bool {
+        self.policies.iter().all(|policy| policy.accepts(password))
+    }
+
+    pub fn count_valid(&self, input: &[Password]) -> usize {
+        input.iter().filter(|password| self.is_valid(password)).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Segmentation;
+
+    fn password<'a>(start: usize, end: usize, letter: &'a str, password: &'a str) -> Password<'a> {
+        Password {
+            line: 1,
+            frequency: start..=end,
+            letter,
+            password,
+            segmentation: Segmentation::Chars,
+        }
+    }
+
+    #[test]
+    fn test_policies() {
+        let pass = password(1, 3, "a", "abcde");
+        assert!(CountInRange.check(&pass).is_ok());
+        assert!(Positions { min: 1, max: 1 }.check(&pass).is_ok());
+        assert!(Positions { min: 0, max: 0 }.check(&pass).is_err());
+        assert!(Forbidden {
+            letters: vec!["x".to_string(), "e".to_string()]
+        }
+        .check(&pass)
+        .is_err());
+        assert!(from_spec("regex:^ab").unwrap().check(&pass).is_ok());
+        assert!(from_spec("require:aab").unwrap().check(&pass).is_err());
+        assert!(from_spec("require:abe").unwrap().check(&pass).is_ok());
+        for spec in [
+            "count",
+            "positions",
+            "at-most:0",
+            "forbid:e",
+            "forbid:x",
+            "regex:^b",
+            "require:aab",
+            "require:abe",
+        ]
+        .iter()
+        {
+            let policy = from_spec(spec).unwrap();
+            assert_eq!(policy.accepts(&pass), policy.check(&pass).is_ok(), "{}", spec);
+        }
+        let out_of_range = password(2, 9, "c", "ccc");
+        assert_eq!(
+            Positions { min: 1, max: 1 }.check(&out_of_range),
+            Err("position 9 is outside the password".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_spec() {
+        for spec in ["count", "positions", "at-most:1", "forbid:xyz", "regex:^[a-z]+$", "require:aab"].iter() {
+            assert_eq!(from_spec(spec).unwrap().name(), *spec);
+        }
+        assert!(matches!(from_spec("at-most:x"), Err(Error::UnknownPolicy { .. })));
+        assert!(matches!(from_spec("forbid:"), Err(Error::UnknownPolicy { .. })));
+        assert!(matches!(from_spec("regex:("), Err(Error::CompilingRegex { .. })));
+        assert!(matches!(from_spec("length"), Err(Error::UnknownPolicy { .. })));
+    }
+
+    #[test]
+    fn test_evaluator() {
+        let evaluator = Evaluator::new(vec![
+            Box::new(CountInRange),
+            Box::new(Forbidden {
+                letters: vec!["b".to_string()],
+            }),
+        ]);
+        let failures = evaluator.evaluate(&password(1, 3, "b", "cdefg"));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].rule, "count");
+        let failures = evaluator.evaluate(&password(1, 3, "a", "abcde"));
+        assert_eq!(failures.iter().map(|f| f.rule.as_str()).collect::<Vec<_>>(), vec!["forbid:b"]);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::policy::Evaluator;
+use crate::{Error, Password, UnknownFormat};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// Output format of the per-password report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => UnknownFormat { format }.fail(),
+        }
+    }
+}
+
+/// Result of one policy for one password.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Outcome {
+    pub rule: String,
+    pub passed: bool,
+    pub reason: Option<String>,
+}
+
+/// Everything observed about one line of the input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Row {
+    pub line: usize,
+    /// The entry's own policy as written, e.g. `1-3 a`
+    pub policy: String,
+    pub password: String,
+    /// Occurrences of the letter in the password
+    pub count: usize,
+    /// Letters found at the two positions, `None` when a position is outside the password
+    pub positions: [Option<String>; 2],
+    pub outcomes: Vec<Outcome>,
+}
+
+impl Row {
+    pub fn new(password: &Password, evaluator: &Evaluator) -> Self {
+        let at = |position: usize| position.checked_sub(1).and_then(|index| password.letters().nth(index)).map(str::to_string);
+        Self {
+            line: password.line,
+            policy: format!("{}-{} {}", password.frequency.start(), password.frequency.end(), password.letter),
+            password: password.password.to_string(),
+            count: password.count(),
+            positions: [at(*password.frequency.start()), at(*password.frequency.end())],
+            outcomes: evaluator
+                .policies()
+                .iter()
+                .map(|policy| {
+                    let result = policy.check(password);
+                    Outcome {
+                        rule: policy.name(),
+                        passed: result.is_ok(),
+                        reason: result.err(),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// The leading columns shared by the table and CSV output, see [`COLUMNS`].
+    fn cells(&self) -> Vec<String> {
+        let positions = self.positions.iter().map(|letter| letter.as_deref().unwrap_or("-")).collect::<Vec<_>>();
+        vec![
+            self.line.to_string(),
+            self.policy.clone(),
+            self.password.clone(),
+            self.count.to_string(),
+            positions.join(" "),
+        ]
+    }
+}
+
+const COLUMNS: [&str; 5] = ["line", "policy", "password", "count", "positions"];
+
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn pass_fail(passed: bool) -> &'static str {
+    if passed {
+        "pass"
+    } else {
+        "fail"
+    }
+}
+
+/// Renders `rows` as an aligned table ending in the number of passwords each policy accepts.
+pub fn table(rules: &[String], rows: &[Row]) -> String {
+    let mut header = COLUMNS.iter().map(|column| column.to_string()).collect::<Vec<_>>();
+    header.extend(rules.iter().cloned());
+    let mut cells = vec![header];
+    for row in rows {
+        let mut line = row.cells();
+        line.extend(row.outcomes.iter().map(|outcome| pass_fail(outcome.passed).to_string()));
+        cells.push(line);
+    }
+    let mut totals = vec![String::new(); COLUMNS.len()];
+    totals[0] = "valid".to_string();
+    totals.extend((0..rules.len()).map(|i| rows.iter().filter(|row| row.outcomes[i].passed).count().to_string()));
+    cells.push(totals);
+
+    let widths = (0..cells[0].len())
+        .map(|column| cells.iter().map(|line| line[column].chars().count()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    let mut out = String::new();
+    for line in &cells {
+        let padded = line
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>();
+        out.push_str(padded.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders `rows` as CSV with a pass/fail column and a reason column per policy.
+pub fn csv(rules: &[String], rows: &[Row]) -> String {
+    let mut header = COLUMNS.iter().map(|column| column.to_string()).collect::<Vec<_>>();
+    for rule in rules {
+        header.push(rule.clone());
+        header.push(format!("{} reason", rule));
+    }
+    let mut out = header.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",");
+    out.push('\n');
+    for row in rows {
+        let mut fields = row.cells();
+        for outcome in &row.outcomes {
+            fields.push(pass_fail(outcome.passed).to_string());
+            fields.push(outcome.reason.clone().unwrap_or_default());
+        }
+        out.push_str(&fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders `rows` as a JSON array, one object per line of the input.
+pub fn json(rows: &[Row]) -> String {
+    serde_json::to_string_pretty(rows).expect("rows serialize to JSON")
+}
+
+/// Evaluates every password against `evaluator` and renders the result in `format`.
+pub fn render(format: Format, evaluator: &Evaluator, input: &[Password]) -> String {
+    let rules = evaluator.policies().iter().map(|policy| policy.name()).collect::<Vec<_>>();
+    let rows = input.iter().map(|password| Row::new(password, evaluator)).collect::<Vec<_>>();
+    match format {
+        Format::Table => table(&rules, &rows),
+        Format::Csv => csv(&rules, &rows),
+        Format::Json => json(&rows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{CountInRange, Positions};
+    use crate::{parse, Segmentation};
+
+    fn rows() -> (Evaluator, Vec<Password<'static>>) {
+        let input = parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc", Segmentation::Chars).expect("parse");
+        (Evaluator::new(vec![Box::new(CountInRange), Box::new(Positions { min: 1, max: 1 })]), input)
+    }
+
+    #[test]
+    fn test_row() {
+        let (evaluator, input) = rows();
+        let row = Row::new(&input[1], &evaluator);
+        assert_eq!(row.policy, "1-3 b");
+        assert_eq!(row.count, 0);
+        assert_eq!(row.positions, [Some("c".to_string()), Some("e".to_string())]);
+        assert_eq!(row.outcomes.iter().map(|outcome| outcome.passed).collect::<Vec<_>>(), vec![false, false]);
+        assert_eq!(row.outcomes[0].reason.as_deref(), Some("`b` occurs 0 times, expected 1 to 3"));
+    }
+
+    #[test]
+    fn test_table() {
+        let (evaluator, input) = rows();
+        let expected = "\
+line   policy  password   count  positions  count  positions
+1      1-3 a   abcde      1      a c        pass   pass
+2      1-3 b   cdefg      0      c e        fail   fail
+3      2-9 c   ccccccccc  9      c c        pass   fail
+valid                                       2      1
+";
+        assert_eq!(render(Format::Table, &evaluator, &input), expected);
+    }
+
+    #[test]
+    fn test_csv() {
+        let (evaluator, input) = rows();
+        let csv = render(Format::Csv, &evaluator, &input);
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "line,policy,password,count,positions,count,count reason,positions,positions reason");
+        assert_eq!(lines[1], "1,1-3 a,abcde,1,a c,pass,,pass,");
+        assert_eq!(lines[3], "3,2-9 c,ccccccccc,9,c c,pass,,fail,\"`c` is at 2 of the positions, expected 1 to 1\"");
+        assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn test_json() {
+        let (evaluator, input) = rows();
+        let json: serde_json::Value = serde_json::from_str(&render(Format::Json, &evaluator, &input)).expect("valid JSON");
+        assert_eq!(json[2]["positions"], serde_json::json!(["c", "c"]));
+        assert_eq!(json[2]["outcomes"][1]["passed"], false);
+        assert_eq!(json[0]["outcomes"][0]["reason"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!("csv".parse::<Format>().unwrap(), Format::Csv);
+        assert!(matches!("xml".parse::<Format>(), Err(Error::UnknownFormat { .. })));
+    }
+}
@@ -0,0 +1,50 @@
+use std::str::CharIndices;
+use unicode_segmentation::{Graphemes, UnicodeSegmentation};
+
+/// How a password is split into the letters that policies count and index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segmentation {
+    /// Every Unicode scalar value is a letter
+    Chars,
+    /// Every extended grapheme cluster is a letter, so `e` followed by a combining accent counts once
+    Graphemes,
+}
+
+impl Segmentation {
+    pub fn letters(self, text: &str) -> Letters<'_> {
+        match self {
+            Segmentation::Chars => Letters::Chars(text, text.char_indices()),
+            Segmentation::Graphemes => Letters::Graphemes(text.graphemes(true)),
+        }
+    }
+}
+
+/// Iterator over the letters of a password as string slices, see [`Segmentation::letters`].
+pub enum Letters<'a> {
+    Chars(&'a str, CharIndices<'a>),
+    Graphemes(Graphemes<'a>),
+}
+
+impl<'a> Iterator for Letters<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Letters::Chars(text, indices) => indices.next().map(|(index, c)| &text[index..index + c.len_utf8()]),
+            Letters::Graphemes(graphemes) => graphemes.next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letters() {
+        let text = "ce\u{301}z";
+        assert_eq!(Segmentation::Chars.letters(text).collect::<Vec<_>>(), vec!["c", "e", "\u{301}", "z"]);
+        assert_eq!(Segmentation::Graphemes.letters(text).collect::<Vec<_>>(), vec!["c", "e\u{301}", "z"]);
+        assert_eq!(Segmentation::Chars.letters("日本").count(), 2);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::policy::Evaluator;
+use crate::{parse_line, Error, Reading, Segmentation};
+use parse::Line;
+use snafu::ResultExt;
+use std::io::BufRead;
+use std::ops::Range;
+
+/// Lines are read into a reused buffer about this many bytes at a time.
+const CHUNK_BYTES: usize = 1 << 20;
+
+/// Number of entries each policy accepts over a stream of entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tally {
+    pub entries: usize,
+    /// Entries accepted by each policy, in the evaluator's order
+    pub valid: Vec<usize>,
+    /// Entries accepted by every policy
+    pub all: usize,
+}
+
+impl Tally {
+    fn new(policies: usize) -> Self {
+        Self {
+            entries: 0,
+            valid: vec![0; policies],
+            all: 0,
+        }
+    }
+
+    fn add(&mut self, line: Line<'_>, evaluator: &Evaluator, segmentation: Segmentation) -> Result<(), parse::Error> {
+        let password = parse_line(line, segmentation)?;
+        let mut all = true;
+        for (valid, policy) in self.valid.iter_mut().zip(evaluator.policies()) {
+            if policy.accepts(&password) {
+                *valid += 1;
+            } else {
+                all = false;
+            }
+        }
+        self.entries += 1;
+        if all {
+            self.all += 1;
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+    fn merge(mut self, other: Self) -> Self {
+        self.entries += other.entries;
+        self.all += other.all;
+        for (valid, other) in self.valid.iter_mut().zip(other.valid) {
+            *valid += other;
+        }
+        self
+    }
+}
+
+/// Reads lines into one reused buffer a chunk at a time, so memory use does not grow with the input.
+///
+/// Input is treated like normalized text: a leading byte order mark and `\r\n` endings are accepted, and
+/// blank lines are only allowed at the end.
+struct Chunks<R> {
+    reader: R,
+    size: usize,
+    buffer: String,
+    /// Line number and byte range within `buffer` of every line in the current chunk
+    lines: Vec<(usize, Range<usize>)>,
+    number: usize,
+    blank: Option<usize>,
+}
+
+impl<R: BufRead> Chunks<R> {
+    fn new(reader: R, size: usize) -> Self {
+        Self {
+            reader,
+            size,
+            buffer: String::with_capacity(size),
+            lines: Vec::new(),
+            number: 0,
+            blank: None,
+        }
+    }
+
+    /// Replaces the current chunk with the next one, returning `false` once the input is exhausted.
+    fn fill(&mut self) -> Result<bool, Error> {
+        self.buffer.clear();
+        self.lines.clear();
+        while self.buffer.len() < self.size {
+            let mut start = self.buffer.len();
+            if self.reader.read_line(&mut self.buffer).context(Reading)? == 0 {
+                break;
+            }
+            self.number += 1;
+            if self.number == 1 && self.buffer[start..].starts_with('\u{feff}') {
+                start += '\u{feff}'.len_utf8();
+            }
+            let text = &self.buffer[start..];
+            let text = text.strip_suffix('\n').unwrap_or(text);
+            let text = text.strip_suffix('\r').unwrap_or(text);
+            let end = start + text.len();
+            if start == end {
+                self.blank.get_or_insert(self.number);
+                self.buffer.truncate(start);
+                continue;
+            }
+            if let Some(blank) = self.blank {
+                return Err(Line::new(blank, "").error_at_end("password entry").into());
+            }
+            self.lines.push((self.number, start..end));
+        }
+        Ok(!self.lines.is_empty())
+    }
+
+    fn lines(&self) -> impl Iterator<Item = Line<'_>> {
+        self.lines.iter().map(move |(number, range)| Line::new(*number, &self.buffer[range.clone()]))
+    }
+}
+
+fn validate_chunks<R: BufRead>(mut chunks: Chunks<R>, evaluator: &Evaluator, segmentation: Segmentation) -> Result<Tally, Error> {
+    let mut tally = Tally::new(evaluator.policies().len());
+    while chunks.fill()? {
+        for line in chunks.lines() {
+            tally.add(line, evaluator, segmentation)?;
+        }
+    }
+    Ok(tally)
+}
+
+/// Validates every entry read from `reader` against `evaluator` without collecting the entries.
+///
+/// Entries borrow from a reused line buffer and are checked with [`Policy::accepts`](crate::policy::Policy::accepts),
+/// so nothing is allocated per password and memory stays constant for any input size.
+pub fn validate<R: BufRead>(reader: R, evaluator: &Evaluator, segmentation: Segmentation) -> Result<Tally, Error> {
+    validate_chunks(Chunks::new(reader, CHUNK_BYTES), evaluator, segmentation)
+}
+
+#[cfg(feature = "parallel")]
+fn validate_chunks_parallel<R: BufRead>(mut chunks: Chunks<R>, evaluator: &Evaluator, segmentation: Segmentation) -> Result<Tally, Error> {
+    use rayon::prelude::*;
+
+    let policies = evaluator.policies().len();
+    let mut tally = Tally::new(policies);
+    while chunks.fill()? {
+        let buffer = &chunks.buffer;
+        let chunk = chunks
+            .lines
+            .par_iter()
+            .map(|(number, range)| Line::new(*number, &buffer[range.clone()]))
+            .fold(
+                || Ok(Tally::new(policies)),
+                |tally: Result<Tally, parse::Error>, line| {
+                    let mut tally = tally?;
+                    tally.add(line, evaluator, segmentation)?;
+                    Ok(tally)
+                },
+            )
+            .reduce(
+                || Ok(Tally::new(policies)),
+                |a, b| match (a, b) {
+                    (Ok(a), Ok(b)) => Ok(a.merge(b)),
+                    // keep the earliest error so the report does not depend on scheduling
+                    (Err(a), Err(b)) => Err(if a.line <= b.line { a } else { b }),
+                    (Err(error), Ok(_)) | (Ok(_), Err(error)) => Err(error),
+                },
+            )?;
+        tally = tally.merge(chunk);
+    }
+    Ok(tally)
+}
+
+/// Like [`validate`], but the entries of each chunk are parsed and checked in parallel.
+#[cfg(feature = "parallel")]
+pub fn validate_parallel<R: BufRead>(reader: R, evaluator: &Evaluator, segmentation: Segmentation) -> Result<Tally, Error> {
+    validate_chunks_parallel(Chunks::new(reader, CHUNK_BYTES), evaluator, segmentation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use crate::policy::{CountInRange, Forbidden, Positions};
+
+    const INPUT: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc";
+
+    fn evaluator() -> Evaluator {
+        Evaluator::new(vec![
+            Box::new(CountInRange),
+            Box::new(Positions { min: 1, max: 1 }),
+            Box::new(Forbidden {
+                letters: vec!["e".to_string()],
+            }),
+        ])
+    }
+
+    fn validate_small(input: &str, size: usize) -> Result<Tally, Error> {
+        validate_chunks(Chunks::new(input.as_bytes(), size), &evaluator(), Segmentation::Chars)
+    }
+
+    #[test]
+    fn test_matches_parse() {
+        let evaluator = evaluator();
+        let input = parse(INPUT, Segmentation::Chars).expect("parse");
+        let expected = Tally {
+            entries: 3,
+            valid: evaluator
+                .policies()
+                .iter()
+                .map(|policy| input.iter().filter(|p| policy.accepts(p)).count())
+                .collect(),
+            all: evaluator.count_valid(&input),
+        };
+        assert_eq!(expected.valid, vec![2, 1, 1]);
+        for size in [1, 5, 20, CHUNK_BYTES].iter() {
+            assert_eq!(validate_small(INPUT, *size).expect("validate"), expected, "chunk size {}", size);
+        }
+        let normalized = format!("\u{feff}{}\r\n\r\n", INPUT.replace('\n', "\r\n"));
+        assert_eq!(validate_small(&normalized, 7).expect("validate"), expected);
+    }
+
+    #[test]
+    fn test_errors() {
+        match validate_small("1-3 a: abcde\n\n1-3 b: cdefg", 4) {
+            Err(Error::Solver {
+                source: error::Error::Parsing { source },
+            }) => assert_eq!(source.line, 2),
+            _ => panic!("expected parse error"),
+        }
+        match validate_small("1-3 a: abcde\n1-3 b: cdefg\n9-1 c: c", 4) {
+            Err(Error::Solver {
+                source: error::Error::Parsing { source },
+            }) => assert_eq!((source.line, source.column), (3, 3)),
+            _ => panic!("expected parse error"),
+        }
+        let invalid: &[u8] = b"1-3 a: ab\xffde";
+        assert!(matches!(validate(invalid, &evaluator(), Segmentation::Chars), Err(Error::Reading { .. })));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel() {
+        let input = INPUT.repeat(1000).replace("ccccccccc1", "ccccccccc\n1");
+        let sequential = validate_small(&input, 64).expect("validate");
+        let parallel = validate_chunks_parallel(Chunks::new(input.as_bytes(), 64), &evaluator(), Segmentation::Chars).expect("validate");
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel.entries, 3000);
+        let broken = format!("{}\n1-3 x\n{}\n0-1 a: a", input, input);
+        match validate_chunks_parallel(Chunks::new(broken.as_bytes(), 1 << 16), &evaluator(), Segmentation::Chars) {
+            Err(Error::Solver {
+                source: error::Error::Parsing { source },
+            }) => assert_eq!(source.line, 3001),
+            _ => panic!("expected parse error"),
+        }
+    }
+}
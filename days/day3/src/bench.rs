@@ -0,0 +1,61 @@
+use crate::forest::Forest;
+use crate::slope::count_trees;
+use crate::{Coordinate, SLOPES};
+use grid::Grid;
+use rng::Rng;
+use std::time::Duration;
+use timing::time;
+
+/// Map of `width` by `height` cells where roughly one in five is a tree, like the puzzle inputs.
+pub fn generate(width: usize, height: usize, seed: u64) -> Grid<Coordinate> {
+    let mut rng = Rng::new(seed);
+    let cells = (0..width * height)
+        .map(|_| if rng.below(5) == 0 { Coordinate::Tree } else { Coordinate::Open })
+        .collect();
+    Grid::new(width, height, cells)
+}
+
+/// How long each measurement repeats its work.
+const RUN: Duration = Duration::from_millis(200);
+
+/// Times the part 2 slopes over generated maps with the cell grid and with bitset rows, the latter both
+/// through the same wrapped lookup as the grid and with the stepping [`Forest::count_trees`].
+pub fn run() {
+    println!(
+        "{:>6} {:>8} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "width", "height", "grid bytes", "bitset bytes", "grid", "bitset", "stepped"
+    );
+    for &(width, height) in [(31, 323), (31, 1 << 20), (1000, 10_000), (100_000, 100)].iter() {
+        let map = generate(width, height, (width * height) as u64);
+        let forest = Forest::from(&map);
+        let (grid_trees, grid_time) = time(RUN, || SLOPES.iter().map(|slope| count_trees(&map, *slope)).sum::<usize>());
+        let (forest_trees, forest_time) = time(RUN, || SLOPES.iter().map(|slope| count_trees(&forest, *slope)).sum::<usize>());
+        let (stepped_trees, stepped_time) = time(RUN, || SLOPES.iter().map(|slope| forest.count_trees(*slope)).sum::<usize>());
+        assert_eq!(grid_trees, forest_trees, "layouts disagree on a {}x{} map", width, height);
+        assert_eq!(grid_trees, stepped_trees, "layouts disagree on a {}x{} map", width, height);
+        println!(
+            "{:>6} {:>8} {:>12} {:>12} {:>12?} {:>12?} {:>12?}",
+            width,
+            height,
+            width * height * std::mem::size_of::<Coordinate>(),
+            forest.size(),
+            grid_time,
+            forest_time,
+            stepped_time
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let map = generate(40, 50, 1);
+        assert_eq!((map.width(), map.height()), (40, 50));
+        let trees = map.iter().filter(|(_, cell)| matches!(cell, Coordinate::Tree)).count();
+        assert!(trees > 200 && trees < 600, "{} trees", trees);
+        assert_eq!(generate(40, 50, 1), map);
+    }
+}
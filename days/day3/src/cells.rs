@@ -0,0 +1,81 @@
+use crate::{Coordinate, Error, InvalidCell, InvalidRagged};
+use grid::Ragged;
+
+/// Characters a map may contain and the cell each stands for: `#` and `.` plus any added with `--cell`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cells {
+    cells: Vec<(char, Coordinate)>,
+}
+
+impl Default for Cells {
+    fn default() -> Self {
+        Self {
+            cells: vec![('#', Coordinate::Tree), ('.', Coordinate::Open)],
+        }
+    }
+}
+
+impl Cells {
+    /// Adds a `c:tree` or `c:open` spec, replacing any earlier meaning of the character.
+    pub fn add(&mut self, spec: &str) -> Result<(), Error> {
+        let mut chars = spec.chars();
+        let cell = match (chars.next(), chars.next(), chars.as_str()) {
+            (Some(c), Some(':'), "tree") => (c, Coordinate::Tree),
+            (Some(c), Some(':'), "open") => (c, Coordinate::Open),
+            _ => return InvalidCell { spec }.fail(),
+        };
+        self.cells.retain(|(c, _)| *c != cell.0);
+        self.cells.push(cell);
+        Ok(())
+    }
+
+    pub fn get(&self, c: char) -> Option<Coordinate> {
+        self.cells.iter().find(|(cell, _)| *cell == c).map(|(_, coordinate)| *coordinate)
+    }
+
+    /// The accepted characters for parse errors, such as "`#`, `.` or `o`".
+    pub fn expected(&self) -> String {
+        let names = self.cells.iter().map(|(c, _)| format!("`{}`", c)).collect::<Vec<_>>();
+        match names.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+            _ => names.concat(),
+        }
+    }
+}
+
+/// Parses a `--ragged` policy: `reject`, `pad` with open cells, or `truncate`.
+pub fn ragged(policy: &str) -> Result<Ragged<Coordinate>, Error> {
+    match policy {
+        "reject" => Ok(Ragged::Reject),
+        "pad" => Ok(Ragged::Pad(Coordinate::Open)),
+        "truncate" => Ok(Ragged::Truncate),
+        _ => InvalidRagged { policy }.fail(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cells() {
+        let mut cells = Cells::default();
+        assert_eq!(cells.expected(), "`#` or `.`");
+        cells.add("o:open").unwrap();
+        cells.add("T:tree").unwrap();
+        cells.add("#:open").unwrap();
+        assert_eq!(cells.get('o'), Some(Coordinate::Open));
+        assert_eq!(cells.get('#'), Some(Coordinate::Open));
+        assert_eq!(cells.get('x'), None);
+        assert_eq!(cells.expected(), "`.`, `o`, `T` or `#`");
+        for spec in ["o", "o:rock", "oo:open", ":"].iter() {
+            assert!(matches!(cells.add(spec), Err(Error::InvalidCell { .. })), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn test_ragged() {
+        assert_eq!(ragged("pad").unwrap(), Ragged::Pad(Coordinate::Open));
+        assert!(matches!(ragged("wrap"), Err(Error::InvalidRagged { .. })));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::slope::{Slope, Trees};
+use crate::Coordinate;
+use grid::Grid;
+
+const BITS: usize = u64::BITS as usize;
+
+/// Compact tree map: every row is a bitset of any width, packed 64 cells to a word in one buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forest {
+    width: usize,
+    height: usize,
+    /// Words per row
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl Forest {
+    /// Bytes used by the packed rows.
+    pub fn size(&self) -> usize {
+        self.words.len() * std::mem::size_of::<u64>()
+    }
+
+    pub fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.stride..(y + 1) * self.stride]
+    }
+
+    fn bit(row: &[u64], x: usize) -> bool {
+        row[x / BITS] >> (x % BITS) & 1 == 1
+    }
+
+    /// Number of trees hit going down `slope`, like [`count_trees`](crate::slope::count_trees) but
+    /// stepping the column instead of wrapping it for every row.
+    pub fn count_trees(&self, slope: Slope) -> usize {
+        if self.width == 0 {
+            return 0;
+        }
+        let step = slope.right.rem_euclid(self.width as isize) as usize;
+        let mut x = 0;
+        let mut trees = 0;
+        for y in (0..self.height).step_by(slope.down) {
+            trees += Self::bit(self.row(y), x) as usize;
+            x += step;
+            if x >= self.width {
+                x -= self.width;
+            }
+        }
+        trees
+    }
+}
+
+impl Trees for Forest {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn wrapping(&self, x: isize, y: usize) -> bool {
+        Self::bit(self.row(y), x.rem_euclid(self.width as isize) as usize)
+    }
+}
+
+impl From<&Grid<Coordinate>> for Forest {
+    fn from(map: &Grid<Coordinate>) -> Self {
+        let stride = map.width().div_ceil(BITS);
+        let mut words = vec![0; stride * map.height()];
+        for ((x, y), cell) in map.iter() {
+            if let Coordinate::Tree = cell {
+                words[y * stride + x / BITS] |= 1 << (x % BITS);
+            }
+        }
+        Self {
+            width: map.width(),
+            height: map.height(),
+            stride,
+            words,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slope::count_trees;
+    use crate::{parse, MAP};
+
+    #[test]
+    fn test_lookup() {
+        let map = parse(MAP).expect("parse");
+        let forest = Forest::from(&map);
+        assert_eq!((forest.width(), forest.height(), forest.size()), (11, 11, 88));
+        for ((x, y), cell) in map.iter() {
+            let tree = matches!(cell, Coordinate::Tree);
+            assert_eq!(forest.wrapping(x as isize, y), tree, "({}, {})", x, y);
+            assert_eq!(forest.wrapping(x as isize - 11, y), tree);
+            assert_eq!(forest.wrapping(x as isize + 22, y), tree);
+        }
+    }
+
+    #[test]
+    fn test_count_trees() {
+        let map = parse(MAP).expect("parse");
+        let forest = Forest::from(&map);
+        for down in 1..=12 {
+            for right in -25..=25 {
+                let slope = Slope::new(right, down);
+                assert_eq!(forest.count_trees(slope), count_trees(&map, slope), "{}", slope);
+                assert_eq!(count_trees(&forest, slope), count_trees(&map, slope), "{}", slope);
+            }
+        }
+        // rows spanning several words, with a partial last word
+        let wide = parse(&MAP.lines().map(|line| line.repeat(13)).collect::<Vec<_>>().join("\n")).expect("parse");
+        let forest = Forest::from(&wide);
+        assert_eq!(forest.row(0).len(), 3);
+        for right in [1, 3, 64, 70, 142, -65].iter() {
+            let slope = Slope::new(*right, 1);
+            assert_eq!(forest.count_trees(slope), count_trees(&wide, slope), "{}", slope);
+        }
+    }
+}
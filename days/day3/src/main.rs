@@ -1,67 +1,133 @@
-use input::Input;
-use snafu::{ResultExt, Snafu};
-use snafu_cli_debug::SnafuCliDebug;
-
-#[derive(Snafu, SnafuCliDebug)]
-pub enum Error {
-    /// Error loading input
-    LoadingInput { source: input::Error },
-    /// Error converting string to integer
-    Parsing { source: std::num::ParseIntError },
+use cells::Cells;
+use forest::Forest;
+use grid::{Grid, Ragged};
+use search::{Bounds, Steps};
+use slope::{count_trees, trace, Slope};
+use std::fmt;
+
+mod bench;
+mod cells;
+mod forest;
+mod search;
+mod slope;
+
+error::day_error! {
+    #[snafu(display("Invalid slope `{}`, expected right/down such as `3/1`", slope))]
+    InvalidSlope { slope: String },
+    #[snafu(display("Invalid slope bounds `{}`, expected low..high such as `-3..3`", bounds))]
+    InvalidBounds { bounds: String },
+    #[snafu(display("Invalid map cell `{}`, expected a character then `:tree` or `:open`", spec))]
+    InvalidCell { spec: String },
+    #[snafu(display("Invalid ragged row policy `{}`, expected reject, pad or truncate", policy))]
+    InvalidRagged { policy: String },
 }
 
+/// The slopes checked in part 2.
+const SLOPES: [Slope; 5] = [Slope::new(1, 1), Slope::new(3, 1), Slope::new(5, 1), Slope::new(7, 1), Slope::new(1, 2)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Coordinate {
     Open,
     Tree,
 }
 
-fn parse(input: &str) -> Vec<Vec<Coordinate>> {
-    input
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|c| match c {
-                    '#' => Coordinate::Tree,
-                    '.' => Coordinate::Open,
-                    _ => unreachable!(),
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>()
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Coordinate::Open => write!(f, "."),
+            Coordinate::Tree => write!(f, "#"),
+        }
+    }
 }
 
-fn calc_tree(input: &Vec<Vec<Coordinate>>, x_step: usize, y_step: usize) -> usize {
-    let mut x = 0;
-    input
-        .iter()
-        .step_by(y_step)
-        .filter(|line| {
-            let rtn = match line[x] {
-                Coordinate::Tree => true,
-                Coordinate::Open => false,
-            };
-            x += x_step;
-            if x >= line.len() {
-                x = x - line.len();
-            }
-            rtn
-        })
-        .count()
+/// Parses a map made of the characters in `cells`, with rows of different widths handled as `ragged` says.
+fn parse_with(input: &str, cells: &Cells, ragged: Ragged<Coordinate>) -> Result<Grid<Coordinate>, Error> {
+    Ok(Grid::parse_ragged(input, &cells.expected(), ragged, |c| cells.get(c))?)
 }
 
-fn part1(input: &Vec<Vec<Coordinate>>) -> usize {
-    calc_tree(input, 3, 1)
+/// Parses the puzzle's own map format: `#` and `.` in rows of equal width.
+#[cfg(test)]
+fn parse(input: &str) -> Result<Grid<Coordinate>, Error> {
+    parse_with(input, &Cells::default(), Ragged::Reject)
 }
 
-fn part2(input: &Vec<Vec<Coordinate>>) -> usize {
-    calc_tree(input, 1, 1) * calc_tree(input, 3, 1) * calc_tree(input, 5, 1) * calc_tree(input, 7, 1) * calc_tree(input, 1, 2)
+/// The example map from the puzzle, shared by the tests of each module.
+#[cfg(test)]
+const MAP: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+fn part1(input: &Grid<Coordinate>) -> usize {
+    count_trees(input, Slope::new(3, 1))
+}
+
+fn part2(input: &Grid<Coordinate>) -> usize {
+    SLOPES.iter().map(|slope| count_trees(input, *slope)).product()
+}
+
+/// Reports the slopes within `--right` and `--down` hitting the fewest and most trees. By default every
+/// distinct slope is searched: moving left is the same as moving right by the width minus the step.
+fn run_search(input: &Grid<Coordinate>) -> Result<(), Error> {
+    let right = match cli::flags("right").last() {
+        Some(right) => right.parse::<Steps<isize>>()?.0,
+        None => 0..=input.width() as isize - 1,
+    };
+    let down = match cli::flags("down").last() {
+        Some(down) => down.parse::<Steps<usize>>()?.0,
+        None => 1..=input.height(),
+    };
+    match search::search(&Forest::from(input), &Bounds { right, down }) {
+        Some(extremes) => {
+            let list = |slopes: &[Slope]| slopes.iter().map(|slope| slope.to_string()).collect::<Vec<_>>().join(", ");
+            println!("Searched {} slopes", extremes.evaluated);
+            println!("Fewest trees {} on {}", extremes.fewest, list(&extremes.fewest_slopes));
+            println!("Most trees {} on {}", extremes.most, list(&extremes.most_slopes));
+        }
+        None => println!("No slopes to search"),
+    }
+    Ok(())
 }
 
 fn main() -> Result<(), Error> {
-    let input = Input::open("config.toml").context(LoadingInput)?.get(3).context(LoadingInput)?;
-    let input = parse(&input);
-    println!("Part 1 {}", part1(&input));
-    println!("Part 2 {}", part2(&input));
+    if cli::has_flag("bench") {
+        bench::run();
+        return Ok(());
+    }
+    let mut cells = Cells::default();
+    for spec in cli::flags("cell") {
+        cells.add(&spec)?;
+    }
+    let ragged = match cli::flags("ragged").last() {
+        Some(policy) => cells::ragged(policy)?,
+        None => Ragged::Reject,
+    };
+    let input = error::load(3)?;
+    let input = parse_with(&input, &cells, ragged)?;
+    if cli::has_flag("search") {
+        return run_search(&input);
+    }
+    let slopes = cli::flags("slope").iter().map(|slope| slope.parse::<Slope>()).collect::<Result<Vec<_>, _>>()?;
+    if slopes.is_empty() {
+        println!("Part 1 {}", part1(&input));
+        println!("Part 2 {}", part2(&input));
+        return Ok(());
+    }
+
+    for slope in slopes {
+        let path = trace(&input, slope);
+        if cli::has_flag("trace") {
+            print!("{}", path.render(&input));
+        }
+        println!("Slope {}: {} trees", slope, path.trees());
+    }
     Ok(())
 }
 
@@ -82,7 +148,7 @@ mod tests {
 #.##...#...
 #...##....#
 .#..#...#.#"#;
-        let input = parse(test_input);
+        let input = parse(test_input).expect("parse");
         assert_eq!(part1(&input), 7);
     }
 
@@ -99,7 +165,46 @@ mod tests {
 #.##...#...
 #...##....#
 .#..#...#.#"#;
-        let input = parse(test_input);
+        let input = parse(test_input).expect("parse");
         assert_eq!(part2(&input), 336);
     }
+
+    #[test]
+    fn test_parse_with() {
+        let mut cells = Cells::default();
+        cells.add("T:tree").unwrap();
+        let input = parse_with("..T\n#.\n.T.#", &cells, Ragged::Pad(Coordinate::Open)).expect("parse");
+        assert_eq!(input.to_string(), "..#.\n#...\n.#.#\n");
+        let input = parse_with("..T\n#.\n.T.#", &cells, Ragged::Truncate).expect("parse");
+        assert_eq!(input.to_string(), "..\n#.\n.#\n");
+        match parse_with("..T\n#.\n", &cells, Ragged::Reject) {
+            Err(Error::Solver {
+                source: error::Error::Parsing { source },
+            }) => assert_eq!((source.line, source.column, source.expected.as_str()), (2, 3, "3 cells")),
+            _ => panic!("expected parse error"),
+        }
+        match parse_with("..T\n#o.\n", &cells, Ragged::Reject) {
+            Err(Error::Solver {
+                source: error::Error::Parsing { source },
+            }) => assert_eq!(source.expected, "`#`, `.` or `T`"),
+            _ => panic!("expected parse error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_crlf() {
+        let input = parse("..#\r\n#..\r\n").expect("parse");
+        assert_eq!((input.width(), input.height()), (3, 2));
+        assert_eq!(input.to_string(), "..#\n#..\n");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        match parse("..#\n.x.") {
+            Err(Error::Solver {
+                source: error::Error::Parsing { source },
+            }) => assert_eq!((source.line, source.column, source.found.as_str()), (2, 2, "x")),
+            _ => panic!("expected parse error"),
+        }
+    }
 }
@@ -0,0 +1,115 @@
+use crate::forest::Forest;
+use crate::slope::{Slope, Trees};
+use crate::{Error, InvalidBounds};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Inclusive range of steps written `low..high`, or a single step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Steps<T>(pub RangeInclusive<T>);
+
+impl<T: FromStr + PartialOrd + Copy> FromStr for Steps<T> {
+    type Err = Error;
+
+    fn from_str(steps: &str) -> Result<Self, Self::Err> {
+        let (low, high) = match steps.find("..") {
+            Some(dots) => (&steps[..dots], &steps[dots + 2..]),
+            None => (steps, steps),
+        };
+        match (low.parse::<T>(), high.parse::<T>()) {
+            (Ok(low), Ok(high)) if low <= high => Ok(Self(low..=high)),
+            _ => InvalidBounds { bounds: steps }.fail(),
+        }
+    }
+}
+
+/// Slopes to search: every `right` step combined with every `down` step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bounds {
+    pub right: RangeInclusive<isize>,
+    pub down: RangeInclusive<usize>,
+}
+
+impl Bounds {
+    fn slopes(&self) -> impl Iterator<Item = Slope> + '_ {
+        self.down
+            .clone()
+            .filter(|down| *down > 0)
+            .flat_map(move |down| self.right.clone().map(move |right| Slope::new(right, down)))
+    }
+}
+
+/// The slopes hitting the fewest and the most trees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extremes {
+    pub evaluated: usize,
+    pub fewest: usize,
+    pub fewest_slopes: Vec<Slope>,
+    pub most: usize,
+    pub most_slopes: Vec<Slope>,
+}
+
+/// Counts the trees on every slope within `bounds`, returning `None` when the map or the bounds are empty.
+pub fn search(forest: &Forest, bounds: &Bounds) -> Option<Extremes> {
+    if forest.width() == 0 {
+        return None;
+    }
+    let mut extremes: Option<Extremes> = None;
+    for slope in bounds.slopes() {
+        let count = forest.count_trees(slope);
+        let extremes = extremes.get_or_insert_with(|| Extremes {
+            evaluated: 0,
+            fewest: count,
+            fewest_slopes: Vec::new(),
+            most: count,
+            most_slopes: Vec::new(),
+        });
+        extremes.evaluated += 1;
+        if count < extremes.fewest {
+            extremes.fewest = count;
+            extremes.fewest_slopes.clear();
+        }
+        if count == extremes.fewest {
+            extremes.fewest_slopes.push(slope);
+        }
+        if count > extremes.most {
+            extremes.most = count;
+            extremes.most_slopes.clear();
+        }
+        if count == extremes.most {
+            extremes.most_slopes.push(slope);
+        }
+    }
+    extremes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slope::count_trees;
+    use crate::{parse, MAP};
+
+    #[test]
+    fn test_steps() {
+        assert_eq!("-3..4".parse::<Steps<isize>>().unwrap(), Steps(-3..=4));
+        assert_eq!("2".parse::<Steps<usize>>().unwrap(), Steps(2..=2));
+        for steps in ["4..3", "a..2", "1..", ""].iter() {
+            assert!(matches!(steps.parse::<Steps<isize>>(), Err(Error::InvalidBounds { .. })), "{}", steps);
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        let map = parse(MAP).expect("parse");
+        let forest = Forest::from(&map);
+        let bounds = Bounds { right: 1..=7, down: 1..=1 };
+        let extremes = search(&forest, &bounds).expect("slopes");
+        assert_eq!(extremes.evaluated, 7);
+        assert_eq!(extremes.most, 7);
+        assert_eq!(extremes.most_slopes, vec![Slope::new(3, 1)]);
+        let brute = (1..=7).map(|right| count_trees(&map, Slope::new(right, 1))).collect::<Vec<_>>();
+        assert_eq!(extremes.fewest, *brute.iter().min().unwrap());
+        assert_eq!(extremes.fewest_slopes.len(), brute.iter().filter(|n| **n == extremes.fewest).count());
+        assert_eq!(search(&forest, &Bounds { right: 0..=3, down: 0..=0 }), None);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{Coordinate, Error, InvalidSlope};
+use grid::Grid;
+use std::fmt;
+use std::str::FromStr;
+
+/// Columns moved right (negative moves left) for every `down` rows.
+///
+/// Written `right/down`, so a rational slope such as `1/2` lands on every second row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slope {
+    pub right: isize,
+    pub down: usize,
+}
+
+impl Slope {
+    pub const fn new(right: isize, down: usize) -> Self {
+        Self { right, down }
+    }
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.right, self.down)
+    }
+}
+
+impl FromStr for Slope {
+    type Err = Error;
+
+    fn from_str(slope: &str) -> Result<Self, Self::Err> {
+        let (right, down) = match slope.find('/') {
+            Some(slash) => (&slope[..slash], &slope[slash + 1..]),
+            None => (slope, "1"),
+        };
+        match (right.parse::<isize>(), down.parse::<usize>()) {
+            (Ok(right), Ok(down)) if down > 0 => Ok(Self { right, down }),
+            _ => InvalidSlope { slope }.fail(),
+        }
+    }
+}
+
+/// A cell the toboggan landed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visit {
+    /// Column before wrapping, so the path can be drawn across repeated copies of the map
+    pub x: isize,
+    pub y: usize,
+    pub tree: bool,
+}
+
+/// Every cell visited going down `slope` from the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub slope: Slope,
+    pub visits: Vec<Visit>,
+}
+
+impl Path {
+    pub fn trees(&self) -> usize {
+        self.visits.iter().filter(|visit| visit.tree).count()
+    }
+
+    /// Draws the visited cells over as many copies of the map as the path crosses, marking them `O`
+    /// when open and `X` when a tree, like the puzzle text.
+    pub fn render(&self, map: &Grid<Coordinate>) -> String {
+        let width = Grid::width(map) as isize;
+        let first = self.visits.iter().map(|visit| visit.x.div_euclid(width)).min().unwrap_or(0);
+        let last = self.visits.iter().map(|visit| visit.x.div_euclid(width)).max().unwrap_or(0);
+        let mut out = String::new();
+        for y in 0..Grid::height(map) {
+            let visited = self.visits.iter().find(|visit| visit.y == y);
+            for x in first * width..(last + 1) * width {
+                out.push(match (visited, Grid::wrapping(map, x, y as isize)) {
+                    (Some(visit), Coordinate::Tree) if visit.x == x => 'X',
+                    (Some(visit), Coordinate::Open) if visit.x == x => 'O',
+                    (_, Coordinate::Tree) => '#',
+                    (_, Coordinate::Open) => '.',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Tree lookup shared by the map layouts, so slopes can be followed over any of them.
+pub trait Trees {
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+
+    /// Whether there is a tree at `(x, y)`, with the map repeating to the left and right.
+    fn wrapping(&self, x: isize, y: usize) -> bool;
+}
+
+impl Trees for Grid<Coordinate> {
+    fn width(&self) -> usize {
+        Grid::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Grid::height(self)
+    }
+
+    fn wrapping(&self, x: isize, y: usize) -> bool {
+        matches!(Grid::wrapping(self, x, y as isize), Coordinate::Tree)
+    }
+}
+
+fn positions<M: Trees>(map: &M, slope: Slope) -> impl Iterator<Item = (isize, usize)> {
+    // a map truncated to no columns has nothing to hit
+    let (height, right) = match map.width() {
+        0 => (0, 0),
+        // whole widths land on the same cells, and the sign is kept so paths going left are drawn that way
+        width => (map.height(), slope.right % width as isize),
+    };
+    (0..height).step_by(slope.down).enumerate().map(move |(step, y)| (step as isize * right, y))
+}
+
+/// Number of trees hit going down `slope`, without recording the path.
+pub fn count_trees<M: Trees>(map: &M, slope: Slope) -> usize {
+    positions(map, slope).filter(|(x, y)| map.wrapping(*x, *y)).count()
+}
+
+pub fn trace<M: Trees>(map: &M, slope: Slope) -> Path {
+    let visits = positions(map, slope)
+        .map(|(x, y)| Visit {
+            x,
+            y,
+            tree: map.wrapping(x, y),
+        })
+        .collect();
+    Path { slope, visits }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, MAP};
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("3/1".parse::<Slope>().unwrap(), Slope::new(3, 1));
+        assert_eq!("-1/2".parse::<Slope>().unwrap(), Slope::new(-1, 2));
+        assert_eq!("5".parse::<Slope>().unwrap(), Slope::new(5, 1));
+        for slope in ["1/0", "x/1", "1/-2", ""].iter() {
+            assert!(matches!(slope.parse::<Slope>(), Err(Error::InvalidSlope { .. })), "{}", slope);
+        }
+        assert_eq!(Slope::new(-1, 2).to_string(), "-1/2");
+    }
+
+    #[test]
+    fn test_trace() {
+        let map = parse(MAP).expect("parse");
+        let path = trace(&map, Slope::new(3, 1));
+        assert_eq!(path.visits.len(), 11);
+        assert_eq!(path.visits[1], Visit { x: 3, y: 1, tree: false });
+        assert_eq!(path.trees(), 7);
+        assert_eq!(trace(&map, Slope::new(1, 2)).visits.len(), 6);
+        for slope in [Slope::new(1, 2), Slope::new(-3, 1), Slope::new(-7, 3), Slope::new(0, 1)].iter() {
+            assert_eq!(trace(&map, *slope).trees(), count_trees(&map, *slope), "{}", slope);
+        }
+        // going left mirrors going right by the width minus the step
+        assert_eq!(count_trees(&map, Slope::new(-8, 1)), count_trees(&map, Slope::new(3, 1)));
+        let path = trace(&map, Slope::new(isize::MAX, 1));
+        assert_eq!(path.visits[2].x, 2 * (isize::MAX % 11));
+        assert_eq!(path.trees(), count_trees(&map, Slope::new(isize::MAX % 11, 1)));
+        assert_eq!(count_trees(&map, Slope::new(isize::MIN, 1)), count_trees(&map, Slope::new(isize::MIN % 11, 1)));
+    }
+
+    #[test]
+    fn test_render() {
+        let map = parse(MAP).expect("parse");
+        let rendered = trace(&map, Slope::new(3, 1)).render(&map);
+        let lines = rendered.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "O.##.........##.........##.......");
+        assert_eq!(lines[1], "#..O#...#..#...#...#..#...#...#..");
+        assert_eq!(lines[2], ".#....X..#..#....#..#..#....#..#.");
+        assert_eq!(lines[10], ".#..#...#.#.#..#...#.#.#..#...X.#");
+        let left = trace(&map, Slope::new(-1, 1)).render(&map);
+        assert!(left.lines().all(|line| line.len() == 22));
+        assert!(left.starts_with("..##.......O.##......."));
+    }
+}
@@ -0,0 +1,47 @@
+use crate::generate::{generate, Mix};
+use crate::passport::Passport;
+use crate::report::Report;
+use crate::schema::{Reason, Schema};
+use crate::{parse, part1, part2};
+use std::hint::black_box;
+use std::time::Duration;
+use timing::time;
+
+/// Minimum time spent on each column of the table.
+const RUN: Duration = Duration::from_millis(200);
+
+/// Times parsing and each way of validating generated batches of growing size, checking the
+/// answers against the generator's ground truth.
+pub fn run() {
+    println!(
+        "{:>9} {:>10} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "passports", "bytes", "parse", "part 1", "part 2", "report", "typed"
+    );
+    let schema = Schema::default();
+    for &count in [1_000, 10_000, 100_000].iter() {
+        let batch = generate(count, &Mix::default(), 30, count as u64);
+        let (_, parse_time) = time(RUN, || parse(black_box(&batch.text)).len());
+        let input = parse(&batch.text);
+        let (complete, part1_time) = time(RUN, || part1(black_box(&input), &schema));
+        let (valid, part2_time) = time(RUN, || part2(black_box(&input), &schema));
+        let (report_valid, report_time) = time(RUN, || Report::new(black_box(&input), &schema).valid);
+        let (typed_valid, typed_time) = time(RUN, || {
+            black_box(&input).iter().filter(|passport| Passport::new(passport, &schema).is_ok()).count()
+        });
+        let missing = batch.expected.iter().filter(|kind| matches!(kind, Some((_, Reason::Missing)))).count();
+        assert_eq!(complete, count - missing, "part 1 disagrees with the ground truth");
+        assert_eq!(valid, batch.valid(), "part 2 disagrees with the ground truth");
+        assert_eq!(report_valid, batch.valid(), "report disagrees with the ground truth");
+        assert_eq!(typed_valid, batch.valid(), "typed conversion disagrees with the ground truth");
+        println!(
+            "{:>9} {:>10} {:>12?} {:>12?} {:>12?} {:>12?} {:>12?}",
+            count,
+            batch.text.len(),
+            parse_time,
+            part1_time,
+            part2_time,
+            report_time,
+            typed_time
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::passport::Passport;
+use crate::{Error, ImportingCsv, ImportingJson, UnknownExport};
+use snafu::ResultExt;
+use std::str::FromStr;
+
+/// Format of an exported batch of valid passports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => UnknownExport { format }.fail(),
+        }
+    }
+}
+
+/// Writes the passports as a JSON array, or as CSV with a header row of the puzzle keys.
+pub fn export(passports: &[Passport], format: Format) -> String {
+    match format {
+        Format::Json => {
+            let mut json = serde_json::to_string_pretty(passports).expect("passports serialize to JSON");
+            json.push('\n');
+            json
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for passport in passports {
+                writer.serialize(passport).expect("passports serialize to CSV");
+            }
+            String::from_utf8(writer.into_inner().expect("CSV is written to memory")).expect("CSV is UTF-8")
+        }
+    }
+}
+
+/// Reads back a batch written by [`export`]. This only converts the field types, so the passports
+/// still have to be checked with [`Passport::validate`].
+pub fn import(text: &str, format: Format) -> Result<Vec<Passport>, Error> {
+    match format {
+        Format::Json => serde_json::from_str(text).context(ImportingJson),
+        Format::Csv => csv::Reader::from_reader(text.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .context(ImportingCsv),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use crate::schema::{Reason, Schema};
+
+    const BATCH: &str = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277";
+
+    fn passports() -> Vec<Passport> {
+        let schema = Schema::default();
+        parse(BATCH).iter().filter_map(|passport| Passport::new(passport, &schema).ok()).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let passports = passports();
+        assert_eq!(passports.len(), 2);
+        for format in [Format::Json, Format::Csv].iter() {
+            let text = export(&passports, *format);
+            assert_eq!(import(&text, *format).expect("import"), passports, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_csv() {
+        let expected = "\
+byr,iyr,eyr,hgt,hcl,ecl,pid,cid
+1980,2012,2030,74in,#623a2f,grn,087499704,
+1989,2014,2029,165cm,#a97842,blu,896056539,129
+";
+        assert_eq!(export(&passports(), Format::Csv), expected);
+    }
+
+    #[test]
+    fn test_import_invalid() {
+        let csv = export(&passports(), Format::Csv).replace("165cm", "165in");
+        let imported = import(&csv, Format::Csv).expect("import");
+        let failures = imported.iter().map(|passport| passport.validate(&Schema::default())).collect::<Vec<_>>();
+        assert_eq!(failures[0], vec![]);
+        assert_eq!(
+            failures[1].iter().map(|failure| (failure.field.as_str(), failure.reason)).collect::<Vec<_>>(),
+            vec![("hgt", Reason::OutOfRange)]
+        );
+        let csv = export(&passports(), Format::Csv).replace("165cm", "165");
+        assert!(matches!(import(&csv, Format::Csv), Err(Error::ImportingCsv { .. })));
+        let json = export(&passports(), Format::Json).replace("\"grn\"", "\"wat\"");
+        assert!(matches!(import(&json, Format::Json), Err(Error::ImportingJson { .. })));
+        assert!(matches!("xml".parse::<Format>(), Err(Error::UnknownExport { .. })));
+    }
+}
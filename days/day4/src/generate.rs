@@ -0,0 +1,281 @@
+use crate::passport::EyeColor;
+use crate::schema::{Bounds, Reason, Rule, Schema};
+use crate::{Error, InvalidMix};
+use rng::Rng;
+use std::str::FromStr;
+
+const REQUIRED: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+
+/// Fields that can break the rules for `reason`.
+fn fields(reason: Reason) -> &'static [&'static str] {
+    match reason {
+        Reason::Missing => &REQUIRED,
+        Reason::Unparseable | Reason::OutOfRange => &["byr", "iyr", "eyr", "hgt"],
+        Reason::WrongUnit => &["hgt"],
+        Reason::BadFormat => &["hcl", "ecl", "pid"],
+    }
+}
+
+/// How often each kind of passport is generated, as relative weights of valid passports and
+/// of passports breaking a single rule for each [`Reason`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mix {
+    weights: Vec<(Option<Reason>, u64)>,
+}
+
+/// Half the passports valid, the rest spread evenly over the reasons.
+impl Default for Mix {
+    fn default() -> Self {
+        let mut weights = vec![(None, Reason::ALL.len() as u64)];
+        weights.extend(Reason::ALL.iter().map(|reason| (Some(*reason), 1)));
+        Self { weights }
+    }
+}
+
+/// Parses `kind:weight` pairs separated by commas, such as `valid:3,missing:1,wrong-unit:1`, where a
+/// kind is `valid` or a reason name; kinds left out are never generated.
+impl FromStr for Mix {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let weights = spec
+            .split(',')
+            .map(|pair| {
+                let (kind, weight) = pair.split_once(':')?;
+                let kind = match kind {
+                    "valid" => None,
+                    _ => Some(*Reason::ALL.iter().find(|reason| reason.name() == kind)?),
+                };
+                Some((kind, weight.parse().ok()?))
+            })
+            .collect::<Option<Vec<_>>>();
+        match weights {
+            Some(weights) if weights.iter().any(|(_, weight)| *weight > 0) => Ok(Self { weights }),
+            _ => InvalidMix { spec }.fail(),
+        }
+    }
+}
+
+impl Mix {
+    fn pick(&self, rng: &mut Rng) -> Option<Reason> {
+        let total = self.weights.iter().map(|(_, weight)| weight).sum();
+        let mut target = rng.below(total);
+        for (kind, weight) in &self.weights {
+            if target < *weight {
+                return *kind;
+            }
+            target -= weight;
+        }
+        unreachable!("target is below the total weight")
+    }
+}
+
+/// A generated batch in the puzzle format with the rule each passport breaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch {
+    pub text: String,
+    /// Field and reason each passport fails with, `None` for a valid passport
+    pub expected: Vec<Option<(&'static str, Reason)>>,
+}
+
+impl Batch {
+    pub fn valid(&self) -> usize {
+        self.expected.iter().filter(|expected| expected.is_none()).count()
+    }
+}
+
+/// A value within the bounds, at either end a quarter of the time each.
+fn within(rng: &mut Rng, bounds: Bounds) -> i64 {
+    match rng.below(4) {
+        0 => bounds.min,
+        1 => bounds.max,
+        _ => bounds.min + rng.below((bounds.max - bounds.min + 1) as u64) as i64,
+    }
+}
+
+/// A value outside the bounds, usually just past one end, and never below `floor`.
+fn outside(rng: &mut Rng, bounds: Bounds, floor: i64) -> i64 {
+    let distance = if rng.percent(50) { 1 } else { 1 + rng.below(100) as i64 };
+    if rng.percent(50) && bounds.min - distance >= floor {
+        bounds.min - distance
+    } else {
+        bounds.max + distance
+    }
+}
+
+/// The numeric bounds of the puzzle's rules, read from the default schema.
+struct Limits {
+    years: Vec<(&'static str, Bounds)>,
+    cm: Bounds,
+    inches: Bounds,
+}
+
+impl Limits {
+    fn new() -> Self {
+        let schema = Schema::default();
+        let rule = |name: &str| &schema.fields.iter().find(|field| field.name == name).expect("puzzle field").rule;
+        let range = |name: &str| match rule(name) {
+            Rule::Range(bounds) => *bounds,
+            rule => panic!("`{}` has a range in the default schema, not {:?}", name, rule),
+        };
+        let (cm, inches) = match rule("hgt") {
+            Rule::Units(units) => (units["cm"], units["in"]),
+            rule => panic!("`hgt` has units in the default schema, not {:?}", rule),
+        };
+        Self {
+            years: ["byr", "iyr", "eyr"].iter().map(|name| (*name, range(name))).collect(),
+            cm,
+            inches,
+        }
+    }
+
+    fn year(&self, field: &str) -> Bounds {
+        self.years.iter().find(|(name, _)| *name == field).expect("year field").1
+    }
+}
+
+fn valid(rng: &mut Rng, limits: &Limits, field: &str) -> String {
+    match field {
+        "byr" | "iyr" | "eyr" => within(rng, limits.year(field)).to_string(),
+        "hgt" if rng.percent(50) => format!("{}cm", within(rng, limits.cm)),
+        "hgt" => format!("{}in", within(rng, limits.inches)),
+        "hcl" => format!("#{:06x}", rng.below(1 << 24)),
+        "ecl" => rng.pick(&EyeColor::ALL).name().to_string(),
+        "pid" => format!("{:09}", rng.below(1_000_000_000)),
+        _ => rng.below(1000).to_string(),
+    }
+}
+
+/// A value of `field` failing with exactly `reason`, which must be one [`fields`] allows.
+fn invalid(rng: &mut Rng, limits: &Limits, field: &str, reason: Reason) -> String {
+    match (field, reason) {
+        ("hgt", Reason::OutOfRange) if rng.percent(50) => format!("{}cm", outside(rng, limits.cm, 0)),
+        ("hgt", Reason::OutOfRange) => format!("{}in", outside(rng, limits.inches, 0)),
+        ("hgt", Reason::Unparseable) => match rng.below(3) {
+            0 => "cm".to_string(),
+            1 => format!("+{}cm", within(rng, limits.cm)),
+            _ => format!("1x{}in", rng.below(10)),
+        },
+        ("hgt", Reason::WrongUnit) => {
+            let height = within(rng, limits.cm);
+            format!("{}{}", height, rng.pick(&["", "mm", "CM", "in."]))
+        }
+        (_, Reason::OutOfRange) => outside(rng, limits.year(field), 1000).to_string(),
+        (_, Reason::Unparseable) => {
+            let year = within(rng, limits.year(field));
+            match rng.below(3) {
+                0 => format!("+{}", year),
+                1 => format!("0{}", year),
+                _ => format!("{}x", year),
+            }
+        }
+        ("hcl", _) => match rng.below(4) {
+            0 => format!("{:06x}", rng.below(1 << 24)),
+            1 => format!("#{:05x}", rng.below(1 << 20)),
+            2 => format!("#{:05x}g", rng.below(1 << 20)),
+            _ => format!("#{:05X}F", rng.below(1 << 20)),
+        },
+        ("ecl", _) => rng.pick(&["wat", "AMB", "", "amber"]).to_string(),
+        _ => match rng.below(3) {
+            0 => format!("{:08}", rng.below(100_000_000)),
+            1 => format!("{:010}", rng.below(10_000_000_000)),
+            _ => format!("{:08}a", rng.below(100_000_000)),
+        },
+    }
+}
+
+/// Generates `count` passports of the given mix, shuffling their fields and starting a new line
+/// before a field `wrap` percent of the time.
+pub fn generate(count: usize, mix: &Mix, wrap: u32, seed: u64) -> Batch {
+    let mut rng = Rng::new(seed);
+    let limits = Limits::new();
+    let mut text = String::new();
+    let mut expected = Vec::with_capacity(count);
+    for index in 0..count {
+        let kind = mix.pick(&mut rng).map(|reason| (rng.pick(fields(reason)), reason));
+        let mut passport = REQUIRED
+            .iter()
+            .copied()
+            .chain(Some("cid").filter(|_| rng.percent(50)))
+            .filter(|field| kind != Some((field, Reason::Missing)))
+            .map(|field| match kind {
+                Some((broken, reason)) if broken == field => (field, invalid(&mut rng, &limits, field, reason)),
+                _ => (field, valid(&mut rng, &limits, field)),
+            })
+            .collect::<Vec<_>>();
+        for i in (1..passport.len()).rev() {
+            passport.swap(i, rng.below(i as u64 + 1) as usize);
+        }
+
+        if index > 0 {
+            text.push_str("\n\n");
+        }
+        for (position, (field, value)) in passport.iter().enumerate() {
+            if position > 0 {
+                text.push_str(if rng.percent(wrap) { "\n" } else { rng.pick(&[" ", " ", " ", "  ", "\t"]) });
+            }
+            text.push_str(field);
+            text.push(':');
+            text.push_str(value);
+        }
+        expected.push(kind);
+    }
+    text.push('\n');
+    Batch { text, expected }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passport::Passport;
+    use crate::schema::Schema;
+    use crate::{parse, part1, part2};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_mix() {
+        let mix = "valid:3,wrong-unit:1".parse::<Mix>().unwrap();
+        assert_eq!(mix.weights, vec![(None, 3), (Some(Reason::WrongUnit), 1)]);
+        for spec in ["", "valid", "valid:0", "valid:x", "broken:1", "valid:1,"].iter() {
+            assert!(matches!(spec.parse::<Mix>(), Err(Error::InvalidMix { .. })), "{}", spec);
+        }
+        let batch = generate(200, &mix, 0, 7);
+        assert!(batch.expected.iter().all(|kind| matches!(kind, None | Some(("hgt", Reason::WrongUnit)))));
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let mix = Mix::default();
+        assert_eq!(generate(50, &mix, 30, 3), generate(50, &mix, 30, 3));
+        assert_ne!(generate(50, &mix, 30, 3), generate(50, &mix, 30, 4));
+        let batch = generate(400, &mix, 0, 1);
+        assert_eq!(batch.text.lines().filter(|line| !line.is_empty()).count(), 400);
+        assert!((120..280).contains(&batch.valid()), "{} valid", batch.valid());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_ground_truth(seed: u64, wrap in 0u32..=100) {
+            let batch = generate(40, &Mix::default(), wrap, seed);
+            let input = parse(&batch.text);
+            let schema = Schema::default();
+            prop_assert_eq!(input.len(), batch.expected.len());
+            for (passport, expected) in input.iter().zip(&batch.expected) {
+                prop_assert!(passport.diagnostics().is_empty(), "{}", passport);
+                let failures = schema
+                    .validate(passport)
+                    .into_iter()
+                    .map(|failure| (failure.field, failure.reason))
+                    .collect::<Vec<_>>();
+                let expected = expected.map(|(field, reason)| (field.to_string(), reason)).into_iter().collect::<Vec<_>>();
+                prop_assert_eq!(&failures, &expected, "{}", passport);
+                prop_assert_eq!(Passport::new(passport, &schema).is_ok(), expected.is_empty());
+            }
+            let missing = batch.expected.iter().filter(|kind| matches!(kind, Some((_, Reason::Missing)))).count();
+            prop_assert_eq!(part1(&input, &schema), batch.expected.len() - missing);
+            prop_assert_eq!(part2(&input, &schema), batch.valid());
+        }
+    }
+}
@@ -1,170 +1,108 @@
-use input::Input;
-use snafu::{OptionExt, ResultExt, Snafu};
-use snafu_cli_debug::SnafuCliDebug;
-use std::collections::HashMap;
-
-#[derive(Snafu, SnafuCliDebug)]
-pub enum Error {
-    /// Error loading input
-    LoadingInput { source: input::Error },
-    /// Error converting string to integer
-    Parsing { source: std::num::ParseIntError },
-    #[snafu(display("Missing required field {}", field))]
-    ObtainingField { field: String },
-}
-
-struct KeyIterator<'a> {
-    line: &'a str,
-    pos: usize,
-}
-
-impl<'a> KeyIterator<'a> {
-    pub fn new(line: &'a str) -> Self {
-        Self { line, pos: 0 }
+use generate::{generate, Mix};
+use passport::{Passport, RawPassport};
+use report::{Format, Report};
+use schema::Schema;
+use snafu::OptionExt;
+
+mod bench;
+mod export;
+mod generate;
+mod passport;
+mod report;
+mod schema;
+
+error::day_error! {
+    #[snafu(display("Error reading passport schema {}", path))]
+    LoadingSchema { source: std::io::Error, path: String },
+    /// Error parsing passport schema
+    ParsingSchema { source: toml::de::Error },
+    #[snafu(display("Unknown report format `{}`, expected table or json", format))]
+    UnknownFormat { format: String },
+    #[snafu(display("Unknown export format `{}`, expected json or csv", format))]
+    UnknownExport { format: String },
+    /// Error importing passports from JSON
+    ImportingJson { source: serde_json::Error },
+    /// Error importing passports from CSV
+    ImportingCsv { source: csv::Error },
+    #[snafu(display("Invalid passport mix `{}`, expected kind:weight pairs such as valid:3,missing:1", spec))]
+    InvalidMix { spec: String },
+    #[snafu(display("Invalid --{} value `{}`, expected a number", flag, value))]
+    InvalidNumber { flag: String, value: String },
+}
+
+fn part1(input: &[RawPassport<'_>], schema: &Schema) -> usize {
+    input.iter().filter(|pass| schema.has_required(pass)).count()
+}
+
+fn part2(input: &[RawPassport<'_>], schema: &Schema) -> usize {
+    input.iter().filter(|pass| schema.is_valid(pass)).count()
+}
+
+fn parse(input: &str) -> Vec<RawPassport<'_>> {
+    parse::records(input).map(passport::parse_record).collect()
+}
+
+/// The last `--name=value` flag as a number, or `default` without one.
+fn number<T: std::str::FromStr>(name: &str, default: T) -> Result<T, Error> {
+    match cli::flags(name).last() {
+        Some(value) => value.parse().ok().context(InvalidNumber { flag: name, value }),
+        None => Ok(default),
     }
 }
 
-enum Height {
-    Inches(isize),
-    Centimetres(isize),
-}
-
-impl<'a> Iterator for KeyIterator<'a> {
-    type Item = (&'a str, &'a str);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let remainder = &self.line[self.pos..];
-        if let Some(colon) = remainder.find(':') {
-            let key = &remainder[..colon];
-            let end = &remainder[colon + 1..];
-            if let Some(space) = end.find(' ') {
-                self.pos += colon + space + 2;
-                let end = &end[..space];
-                Some((key, end))
-            } else {
-                self.pos += colon + end.len() + 1;
-                Some((key, end))
-            }
-        } else {
-            None
-        }
-    }
+/// Prints a generated batch, and how many of its passports are valid on stderr.
+fn run_generate(count: usize) -> Result<(), Error> {
+    let mix = match cli::flags("mix").last() {
+        Some(spec) => spec.parse::<Mix>()?,
+        None => Mix::default(),
+    };
+    let batch = generate(count, &mix, number("wrap", 30)?, number("seed", 1)?);
+    print!("{}", batch.text);
+    eprintln!("{} of {} valid", batch.valid(), count);
+    Ok(())
 }
 
-fn parse_height(hgt: &str) -> Result<Option<Height>, Error> {
-    if let Some(inches) = hgt.find("in") {
-        let value = (&hgt[..inches]).parse::<isize>().context(Parsing)?;
-        if &hgt[inches..] == "in" {
-            Ok(Some(Height::Inches(value)))
-        } else {
-            Ok(None)
-        }
-    } else if let Some(cm) = hgt.find("cm") {
-        let value = (&hgt[..cm]).parse::<isize>().context(Parsing)?;
-        if &hgt[cm..] == "cm" {
-            Ok(Some(Height::Centimetres(value)))
-        } else {
-            Ok(None)
-        }
-    } else {
-        Ok(None)
+fn main() -> Result<(), Error> {
+    if cli::has_flag("bench") {
+        bench::run();
+        return Ok(());
     }
-}
-
-fn verify_hcl(hcl: &str) -> bool {
-    hcl.starts_with('#') && hcl.len() == 7 && (&hcl[1..]).chars().filter(|c| c.is_alphanumeric()).count() == 6
-}
-
-fn verify_ecl(ecl: &str) -> bool {
-    let valid = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
-    valid.iter().filter(|e| ecl == **e).count() == 1
-}
-
-fn verify_pid(pid: &str) -> bool {
-    pid.chars().filter(|c| c.is_numeric()).count() == 9
-}
-
-fn verify_hgt(hgt: &Height) -> bool {
-    match *hgt {
-        Height::Centimetres(val) => val >= 150 && val <= 193,
-        Height::Inches(val) => val >= 59 && val <= 76,
+    if cli::flags("generate").last().is_some() {
+        return run_generate(number("generate", 0)?);
     }
-}
-
-fn verify_field_values(passport: &HashMap<&str, &str>) -> Result<bool, Error> {
-    let byr = passport
-        .get("byr")
-        .context(ObtainingField { field: "byr" })?
-        .parse::<isize>()
-        .context(Parsing)?;
-    let iyr = passport
-        .get("iyr")
-        .context(ObtainingField { field: "iyr" })?
-        .parse::<isize>()
-        .context(Parsing)?;
-    let eyr = passport
-        .get("eyr")
-        .context(ObtainingField { field: "eyr" })?
-        .parse::<isize>()
-        .context(Parsing)?;
-
-    let hgt = parse_height(passport.get("hgt").context(ObtainingField { field: "hgt" })?)?.context(ObtainingField { field: "hgt" })?;
-    let hcl = *passport.get("hcl").context(ObtainingField { field: "hcl" })?;
-    let ecl = *passport.get("ecl").context(ObtainingField { field: "ecl" })?;
-    let pid = *passport.get("pid").context(ObtainingField { field: "pid" })?;
-
-    let byr = byr >= 1920 && byr <= 2002;
-    let iyr = iyr >= 2010 && iyr <= 2020;
-    let eyr = eyr >= 2020 && eyr <= 2030;
-    let hgt = verify_hgt(&hgt);
-    let hcl = verify_hcl(hcl);
-    let ecl = verify_ecl(ecl);
-    let pid = verify_pid(pid);
-
-    Ok(byr && iyr && eyr && hgt && hcl && ecl && pid)
-}
-
-fn verify_fields(passport: &HashMap<&str, &str>) -> bool {
-    let required_keys = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
-    required_keys.iter().filter(|key| passport.contains_key(**key)).count() == required_keys.len()
-}
-
-fn part1(input: &[HashMap<&str, &str>]) -> usize {
-    input.iter().filter(|pass| verify_fields(*pass)).count()
-}
-
-fn part2(input: &[HashMap<&str, &str>]) -> usize {
-    input
-        .iter()
-        .filter(|pass| match verify_field_values(*pass) {
-            Ok(valid) => valid,
-            Err(_e) => false,
-        })
-        .count()
-}
-
-fn parse(input: &str) -> Vec<HashMap<&str, &str>> {
-    let mut rtn = Vec::new();
-    let mut current = HashMap::new();
-    for line in input.lines() {
-        if line.is_empty() {
-            rtn.push(current);
-            current = HashMap::new();
-        } else {
-            KeyIterator::new(line).for_each(|(key, value)| {
-                current.insert(key, value);
-            });
+    let schema = match cli::flags("schema").last() {
+        Some(path) => Schema::load(path)?,
+        None => Schema::default(),
+    };
+    let input = error::load(4)?;
+    if let Some(format) = cli::flags("import").last() {
+        let imported = export::import(&input, format.parse()?)?;
+        let count = imported.len();
+        let passports = imported
+            .into_iter()
+            .filter(|passport| passport.validate(&schema).is_empty())
+            .collect::<Vec<_>>();
+        match cli::flags("export").last() {
+            Some(format) => print!("{}", export::export(&passports, format.parse()?)),
+            None => println!("{} of {} imported passports valid", passports.len(), count),
         }
+        return Ok(());
     }
-    rtn.push(current);
-    rtn
-}
-
-fn main() -> Result<(), Error> {
-    let input = Input::open("config.toml").context(LoadingInput)?.get(4).context(LoadingInput)?;
     let input = parse(&input);
-    println!("Part 1 {}", part1(&input));
-    println!("Part 2 {}", part2(&input));
+    for diagnostic in input.iter().flat_map(RawPassport::diagnostics) {
+        eprintln!("{}", diagnostic);
+    }
+    if let Some(format) = cli::flags("report").last() {
+        print!("{}", Report::new(&input, &schema).render(format.parse::<Format>()?));
+        return Ok(());
+    }
+    if let Some(format) = cli::flags("export").last() {
+        let passports = input.iter().filter_map(|passport| Passport::new(passport, &schema).ok()).collect::<Vec<_>>();
+        print!("{}", export::export(&passports, format.parse()?));
+        return Ok(());
+    }
+    println!("Part 1 {}", part1(&input, &schema));
+    println!("Part 2 {}", part2(&input, &schema));
     Ok(())
 }
 
@@ -189,7 +127,7 @@ hcl:#cfa07d eyr:2025 pid:166559648
 iyr:2011 ecl:brn hgt:59in"#;
         let input = parse(test_input);
         assert_eq!(input.len(), 4);
-        assert_eq!(part1(&input), 2);
+        assert_eq!(part1(&input, &Schema::default()), 2);
     }
 
     #[test]
@@ -208,7 +146,7 @@ hgt:59cm ecl:zzz
 eyr:2038 hcl:74454a iyr:2023
 pid:3556412378 byr:2007"#;
         let input = parse(test_input);
-        assert_eq!(part2(&input), 0);
+        assert_eq!(part2(&input, &Schema::default()), 0);
     }
 
     #[test]
@@ -226,6 +164,34 @@ eyr:2022
 
 iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719"#;
         let input = parse(test_input);
-        assert_eq!(part2(&input), 4);
+        assert_eq!(part2(&input, &Schema::default()), 4);
+    }
+
+    #[test]
+    fn test_parse_blank_lines() {
+        let test_input = "\r\necl:gry pid:860033327\r\nbyr:1937\r\n\r\n\r\niyr:2013 ecl:amb\r\n\r\n";
+        let input = parse(test_input);
+        assert_eq!(input.len(), 2);
+        assert_eq!(input[0].get("pid"), Some("860033327"));
+        assert_eq!(input[0].get("byr"), Some("1937"));
+        assert_eq!(input[1].get("ecl"), Some("amb"));
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let input = parse("ecl:gry\n\niyr:2013  ecl amb\n\necl:gry\tpid:1\necl:amb");
+        assert_eq!(input.len(), 3);
+        let diagnostics = input
+            .iter()
+            .map(|passport| {
+                passport
+                    .diagnostics()
+                    .iter()
+                    .map(|error| (error.line, error.column, error.found.as_str()))
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        assert_eq!(diagnostics, vec![vec![], vec![(3, 11, "ecl"), (3, 15, "amb")], vec![(6, 1, "ecl")]]);
+        assert_eq!(input[2].get("ecl"), Some("gry"));
     }
 }
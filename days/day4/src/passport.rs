@@ -0,0 +1,483 @@
+use crate::schema::{Failure, Reason, Schema};
+use parse::{Cursor, Line, Record};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::str::FromStr;
+
+/// A passport's `key:value` fields as written, in input order, with the problems found parsing them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RawPassport<'a> {
+    fields: Vec<(&'a str, &'a str)>,
+    diagnostics: Vec<parse::Error>,
+}
+
+impl<'a> RawPassport<'a> {
+    /// Value of the first field named `key`.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.fields.iter().find(|(name, _)| *name == key).map(|(_, value)| *value)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.fields.iter().copied()
+    }
+
+    /// Tokens that were not `key:value` fields and keys given again, which were left out of the fields.
+    pub fn diagnostics(&self) -> &[parse::Error] {
+        &self.diagnostics
+    }
+}
+
+impl<'a> FromIterator<(&'a str, &'a str)> for RawPassport<'a> {
+    fn from_iter<I: IntoIterator<Item = (&'a str, &'a str)>>(iter: I) -> Self {
+        Self {
+            fields: iter.into_iter().collect(),
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+/// Writes the fields on one line in their original order, which parses back to the same passport.
+impl fmt::Display for RawPassport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (key, value)) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}:{}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A number of plain ASCII digits that fits in `T`.
+fn number<T: FromStr>(value: &str) -> Result<T, Reason> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Reason::Unparseable);
+    }
+    value.parse().map_err(|_| Reason::OutOfRange)
+}
+
+/// Height in one of the two units the puzzle knows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Height {
+    Cm(u16),
+    In(u16),
+}
+
+impl FromStr for Height {
+    type Err = Reason;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Some(number) = value.strip_suffix("cm") {
+            Ok(Height::Cm(self::number(number)?))
+        } else if let Some(number) = value.strip_suffix("in") {
+            Ok(Height::In(self::number(number)?))
+        } else {
+            Err(Reason::WrongUnit)
+        }
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Height::Cm(cm) => write!(f, "{}cm", cm),
+            Height::In(inches) => write!(f, "{}in", inches),
+        }
+    }
+}
+
+/// An RGB colour written as `#` and six hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HairColor(pub [u8; 3]);
+
+impl FromStr for HairColor {
+    type Err = Reason;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let hex = value.strip_prefix('#').ok_or(Reason::BadFormat)?;
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(Reason::BadFormat);
+        }
+        let mut rgb = [0; 3];
+        for (index, channel) in rgb.iter_mut().enumerate() {
+            *channel = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|_| Reason::BadFormat)?;
+        }
+        Ok(HairColor(rgb))
+    }
+}
+
+impl fmt::Display for HairColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EyeColor {
+    Amb,
+    Blu,
+    Brn,
+    Gry,
+    Grn,
+    Hzl,
+    Oth,
+}
+
+impl EyeColor {
+    pub const ALL: [EyeColor; 7] = [
+        EyeColor::Amb,
+        EyeColor::Blu,
+        EyeColor::Brn,
+        EyeColor::Gry,
+        EyeColor::Grn,
+        EyeColor::Hzl,
+        EyeColor::Oth,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EyeColor::Amb => "amb",
+            EyeColor::Blu => "blu",
+            EyeColor::Brn => "brn",
+            EyeColor::Gry => "gry",
+            EyeColor::Grn => "grn",
+            EyeColor::Hzl => "hzl",
+            EyeColor::Oth => "oth",
+        }
+    }
+}
+
+impl FromStr for EyeColor {
+    type Err = Reason;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL.iter().copied().find(|color| color.name() == value).ok_or(Reason::BadFormat)
+    }
+}
+
+impl fmt::Display for EyeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Serializes a field type as the text it was parsed from, so exports hold the puzzle's own notation.
+macro_rules! serde_text {
+    ($($name:ident),*) => {
+        $(
+            impl Serialize for $name {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $name {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let text = String::deserialize(deserializer)?;
+                    text.parse().map_err(|reason: Reason| de::Error::custom(format!("{} `{}`", reason.name(), text)))
+                }
+            }
+        )*
+    };
+}
+
+serde_text!(Height, HairColor, EyeColor);
+
+/// A passport the schema accepted, with the puzzle's fields in their typed form.
+///
+/// Serialized fields keep their puzzle keys. Deserializing only converts the types, so an imported
+/// passport has to be checked with [`Passport::validate`] again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Passport {
+    #[serde(rename = "byr")]
+    pub birth_year: u16,
+    #[serde(rename = "iyr")]
+    pub issue_year: u16,
+    #[serde(rename = "eyr")]
+    pub expiration_year: u16,
+    #[serde(rename = "hgt")]
+    pub height: Height,
+    #[serde(rename = "hcl")]
+    pub hair_color: HairColor,
+    #[serde(rename = "ecl")]
+    pub eye_color: EyeColor,
+    /// Kept as text for its leading zeros
+    #[serde(rename = "pid")]
+    pub passport_id: String,
+    #[serde(rename = "cid")]
+    pub country_id: Option<String>,
+}
+
+impl Passport {
+    /// Checks `passport` against `schema` and fails with the first field breaking it, as
+    /// [`Schema::validate`] reports it, or with a field the schema allows but the type cannot hold.
+    pub fn new(passport: &RawPassport<'_>, schema: &Schema) -> Result<Self, Failure> {
+        if let Some(failure) = schema.validate(passport).into_iter().next() {
+            return Err(failure);
+        }
+        fn field<T>(passport: &RawPassport<'_>, name: &str, parse: impl FnOnce(&str) -> Result<T, Reason>) -> Result<T, Failure> {
+            let value = passport.get(name).ok_or_else(|| failure(name, None, Reason::Missing))?;
+            parse(value).map_err(|reason| failure(name, Some(value), reason))
+        }
+        Ok(Self {
+            birth_year: field(passport, "byr", number)?,
+            issue_year: field(passport, "iyr", number)?,
+            expiration_year: field(passport, "eyr", number)?,
+            height: field(passport, "hgt", str::parse)?,
+            hair_color: field(passport, "hcl", str::parse)?,
+            eye_color: field(passport, "ecl", str::parse)?,
+            passport_id: field(passport, "pid", |value| Ok(value.to_string()))?,
+            country_id: passport.get("cid").map(str::to_string),
+        })
+    }
+
+    /// The fields in puzzle notation and order.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("byr", self.birth_year.to_string()),
+            ("iyr", self.issue_year.to_string()),
+            ("eyr", self.expiration_year.to_string()),
+            ("hgt", self.height.to_string()),
+            ("hcl", self.hair_color.to_string()),
+            ("ecl", self.eye_color.to_string()),
+            ("pid", self.passport_id.clone()),
+        ];
+        fields.extend(self.country_id.clone().map(|cid| ("cid", cid)));
+        fields
+    }
+
+    /// Every field breaking `schema`, checked on the passport written back in puzzle notation.
+    pub fn validate(&self, schema: &Schema) -> Vec<Failure> {
+        let fields = self.fields();
+        schema.validate(&fields.iter().map(|(key, value)| (*key, value.as_str())).collect())
+    }
+}
+
+fn failure(field: &str, value: Option<&str>, reason: Reason) -> Failure {
+    Failure {
+        field: field.to_string(),
+        value: value.map(str::to_string),
+        reason,
+    }
+}
+
+/// Splits a line into `key:value` fields separated by any run of whitespace.
+///
+/// Values may contain further colons; a token without a colon or with an empty key is an error
+/// pointing at the token, and the fields after it are still read.
+pub struct KeyIterator<'a> {
+    cursor: Cursor<'a>,
+}
+
+impl<'a> KeyIterator<'a> {
+    pub fn new(line: Line<'a>) -> Self {
+        Self { cursor: line.cursor() }
+    }
+}
+
+impl<'a> Iterator for KeyIterator<'a> {
+    type Item = Result<(&'a str, &'a str), parse::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.whitespace();
+        if self.cursor.is_empty() {
+            return None;
+        }
+        Some(self.cursor.key_value(':').map_err(|_| {
+            // key_value left the cursor at the start of a non-empty token
+            let token = self.cursor.word().expect("token after whitespace");
+            self.cursor.line().error(token, "`key:value`")
+        }))
+    }
+}
+
+/// Parses the fields of one passport. Malformed tokens and keys given again are reported in
+/// [`RawPassport::diagnostics`], keeping the first value of each key.
+pub fn parse_record(record: Record<'_>) -> RawPassport<'_> {
+    let mut seen = HashMap::new();
+    let mut passport = RawPassport::default();
+    for line in record.lines() {
+        for field in KeyIterator::new(line) {
+            match field {
+                Ok((key, value)) => match seen.get(key) {
+                    Some(first) => {
+                        let expected = format!("a new key, `{}` was already given on line {}", key, first);
+                        passport.diagnostics.push(line.error(key, expected));
+                    }
+                    None => {
+                        seen.insert(key, line.number());
+                        passport.fields.push((key, value));
+                    }
+                },
+                Err(error) => passport.diagnostics.push(error),
+            }
+        }
+    }
+    passport
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Schema;
+
+    fn keys(text: &str) -> Result<Vec<(&str, &str)>, parse::Error> {
+        KeyIterator::new(Line::new(1, text)).collect()
+    }
+
+    #[test]
+    fn test_whitespace() {
+        assert_eq!(keys("a:1 b:2").unwrap(), vec![("a", "1"), ("b", "2")]);
+        assert_eq!(keys("  a:1 \t\tb:2\t ").unwrap(), vec![("a", "1"), ("b", "2")]);
+        assert_eq!(keys("url:http://x a:").unwrap(), vec![("url", "http://x"), ("a", "")]);
+        assert_eq!(keys(" \t ").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let error = keys("a:1\t\tnocolon b:2").unwrap_err();
+        assert_eq!((error.line, error.column, error.found.as_str()), (1, 6, "nocolon"));
+        let error = keys("a:1 :2").unwrap_err();
+        assert_eq!((error.column, error.found.as_str(), error.expected.as_str()), (5, ":2", "`key:value`"));
+        let tokens = KeyIterator::new(Line::new(1, "a:1 :2 b:2")).collect::<Vec<_>>();
+        assert_eq!((tokens.len(), tokens[2].clone().unwrap()), (3, ("b", "2")));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let record = parse::records("\n\nbyr:1 iyr:2\nhgt:3  byr:4 oops pid:5").next().unwrap();
+        let passport = parse_record(record);
+        assert_eq!(passport.to_string(), "byr:1 iyr:2 hgt:3 pid:5");
+        let diagnostics = passport
+            .diagnostics()
+            .iter()
+            .map(|error| (error.line, error.column, error.found.as_str(), error.expected.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![(4, 8, "byr", "a new key, `byr` was already given on line 3"), (4, 14, "oops", "`key:value`")]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let record = parse::records("pid:1 ecl:gry\n\thcl:#fff  byr:2").next().unwrap();
+        let passport = parse_record(record);
+        assert_eq!(passport.iter().map(|(key, _)| key).collect::<Vec<_>>(), vec!["pid", "ecl", "hcl", "byr"]);
+        assert_eq!(passport.get("hcl"), Some("#fff"));
+        let text = passport.to_string();
+        assert_eq!(text, "pid:1 ecl:gry hcl:#fff byr:2");
+        assert_eq!(parse_record(parse::records(&text).next().unwrap()), passport);
+    }
+
+    fn raw(text: &str) -> RawPassport<'_> {
+        parse_record(parse::records(text).next().unwrap())
+    }
+
+    #[test]
+    fn test_fields() {
+        assert_eq!("183cm".parse(), Ok(Height::Cm(183)));
+        assert_eq!("58in".parse(), Ok(Height::In(58)));
+        assert_eq!("70000in".parse::<Height>(), Err(Reason::OutOfRange));
+        assert_eq!("183".parse::<Height>(), Err(Reason::WrongUnit));
+        assert_eq!("+60in".parse::<Height>(), Err(Reason::Unparseable));
+        assert_eq!(Height::In(74).to_string(), "74in");
+        assert_eq!("#0a7f00".parse(), Ok(HairColor([0x0a, 0x7f, 0x00])));
+        assert_eq!("#0A7F00".parse(), Ok(HairColor([0x0a, 0x7f, 0x00])));
+        assert_eq!(HairColor([0x0a, 0x7f, 0x00]).to_string(), "#0a7f00");
+        for color in ["0a7f00", "#0a7f0", "#0a7f000", "#0a7g00", "#+a7f00"].iter() {
+            assert_eq!(color.parse::<HairColor>(), Err(Reason::BadFormat), "{}", color);
+        }
+        for color in EyeColor::ALL.iter() {
+            assert_eq!(color.to_string().parse(), Ok(*color));
+        }
+        assert_eq!("wat".parse::<EyeColor>(), Err(Reason::BadFormat));
+    }
+
+    fn convert(text: &str) -> Result<Passport, Failure> {
+        Passport::new(&raw(text), &Schema::default())
+    }
+
+    #[test]
+    fn test_conversion() {
+        let passport = convert("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f").unwrap();
+        assert_eq!(
+            passport,
+            Passport {
+                birth_year: 1980,
+                issue_year: 2012,
+                expiration_year: 2030,
+                height: Height::In(74),
+                hair_color: HairColor([0x62, 0x3a, 0x2f]),
+                eye_color: EyeColor::Grn,
+                passport_id: "087499704".to_string(),
+                country_id: None,
+            }
+        );
+        assert!(passport.validate(&Schema::default()).is_empty());
+        let failure = convert("eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926").unwrap_err();
+        assert_eq!(
+            (failure.field.as_str(), failure.value.as_deref(), failure.reason),
+            ("eyr", Some("1972"), Reason::OutOfRange)
+        );
+        let failure = convert("hcl:#18171d ecl:amb hgt:170cm iyr:2018 byr:1926 eyr:2020").unwrap_err();
+        assert_eq!((failure.field.as_str(), failure.value, failure.reason), ("pid", None, Reason::Missing));
+    }
+
+    #[test]
+    fn test_agrees_with_schema() {
+        let schema = Schema::default();
+        let batch = "byr:2002 iyr:2010 eyr:2030 hgt:193cm hcl:#abcdef ecl:oth pid:000000001 cid:x
+byr:2003 iyr:2010 eyr:2030 hgt:193cm hcl:#abcdef ecl:oth pid:000000001
+byr:1920 iyr:2020 eyr:2020 hgt:59in hcl:#000000 ecl:amb pid:999999999
+byr:1920 iyr:2020 eyr:2020 hgt:59 hcl:#000000 ecl:amb pid:999999999
+byr:1920 iyr:2020 eyr:2020 hgt:150cm hcl:#00000g ecl:amb pid:999999999
+byr:1920 iyr:2020 eyr:2020 hgt:150cm hcl:#000000 ecl:amb pid:99999999
+byr:01920 iyr:2020 eyr:2020 hgt:150cm hcl:#000000 ecl:amb pid:999999999";
+        for line in batch.lines() {
+            let passport = raw(line);
+            let converted = Passport::new(&passport, &schema);
+            assert_eq!(converted.is_ok(), schema.is_valid(&passport), "{}", line);
+            assert_eq!(converted.err(), schema.validate(&passport).into_iter().next(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_custom_schema() {
+        let passport = raw("byr:1980 iyr:2012 eyr:2030 hgt:2m hcl:#623a2f ecl:grn pid:087499704");
+        let failure = convert(&passport.to_string()).unwrap_err();
+        assert_eq!((failure.field.as_str(), failure.reason), ("hgt", Reason::WrongUnit));
+        let schema = include_str!("../schema.toml").replace("in = {", "m = { min = 1, max = 2 }, in = {");
+        let schema = schema.parse::<Schema>().unwrap();
+        assert!(schema.is_valid(&passport));
+        let failure = Passport::new(&passport, &schema).unwrap_err();
+        assert_eq!((failure.field.as_str(), failure.reason), ("hgt", Reason::WrongUnit));
+    }
+
+    #[test]
+    fn test_serde() {
+        let schema = Schema::default();
+        let passport = convert("byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704 cid:7").unwrap();
+        let json = serde_json::to_string(&passport).unwrap();
+        assert_eq!(
+            json,
+            r##"{"byr":1980,"iyr":2012,"eyr":2030,"hgt":"74in","hcl":"#623a2f","ecl":"grn","pid":"087499704","cid":"7"}"##
+        );
+        assert_eq!(serde_json::from_str::<Passport>(&json).unwrap(), passport);
+        for (from, to, field) in [(r#""byr":1980"#, r#""byr":1919"#, "byr"), (r#""087499704""#, r#""87499704""#, "pid")].iter() {
+            let imported = serde_json::from_str::<Passport>(&json.replace(from, to)).unwrap();
+            let failures = imported.validate(&schema);
+            assert_eq!(failures.iter().map(|failure| failure.field.as_str()).collect::<Vec<_>>(), vec![*field]);
+        }
+        let error = serde_json::from_str::<Passport>(&json.replace(r#""74in""#, r#""74""#)).unwrap_err();
+        assert!(error.is_data(), "{}", error);
+    }
+}
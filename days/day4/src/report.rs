@@ -0,0 +1,193 @@
+use crate::passport::RawPassport;
+use crate::schema::{Failure, Reason, Schema};
+use crate::{Error, UnknownFormat};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Output format of the validation report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            _ => UnknownFormat { format }.fail(),
+        }
+    }
+}
+
+/// Validation outcome of one passport of the batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    /// Position of the passport in the batch, from 1
+    pub passport: usize,
+    pub valid: bool,
+    pub failures: Vec<Failure>,
+}
+
+/// Every passport's failures, with how often each field failed for each reason.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub passports: Vec<Entry>,
+    pub valid: usize,
+    /// Failures per reason over the whole batch
+    pub reasons: BTreeMap<Reason, usize>,
+    /// Failures per field and reason
+    pub fields: BTreeMap<String, BTreeMap<Reason, usize>>,
+}
+
+impl Report {
+    pub fn new(input: &[RawPassport<'_>], schema: &Schema) -> Self {
+        let mut reasons = BTreeMap::new();
+        let mut fields = BTreeMap::new();
+        let passports = input
+            .iter()
+            .enumerate()
+            .map(|(index, passport)| {
+                let failures = schema.validate(passport);
+                for failure in &failures {
+                    *reasons.entry(failure.reason).or_insert(0) += 1;
+                    *fields
+                        .entry(failure.field.clone())
+                        .or_insert_with(BTreeMap::new)
+                        .entry(failure.reason)
+                        .or_insert(0) += 1;
+                }
+                Entry {
+                    passport: index + 1,
+                    valid: failures.is_empty(),
+                    failures,
+                }
+            })
+            .collect::<Vec<_>>();
+        Self {
+            valid: passports.iter().filter(|entry| entry.valid).count(),
+            passports,
+            reasons,
+            fields,
+        }
+    }
+
+    /// Renders one line per passport followed by the histogram of reasons per field.
+    pub fn table(&self) -> String {
+        let mut out = format!("{:>8}  {:<5}  failures\n", "passport", "valid");
+        for entry in &self.passports {
+            let failures = entry.failures.iter().map(Failure::to_string).collect::<Vec<_>>();
+            let line = format!("{:>8}  {:<5}  {}", entry.passport, if entry.valid { "yes" } else { "no" }, failures.join(", "));
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out.push_str(&format!("{} of {} valid\n\n", self.valid, self.passports.len()));
+
+        let widths = Reason::ALL.iter().map(|reason| reason.name().len()).collect::<Vec<_>>();
+        let mut header = format!("{:<5}", "field");
+        for (reason, width) in Reason::ALL.iter().zip(&widths) {
+            header.push_str(&format!("  {:>width$}", reason.name(), width = width));
+        }
+        out.push_str(&header);
+        out.push('\n');
+        let rows = self
+            .fields
+            .iter()
+            .map(|(field, counts)| (field.as_str(), counts))
+            .chain(std::iter::once(("total", &self.reasons)));
+        for (field, counts) in rows {
+            let mut line = format!("{:<5}", field);
+            for (reason, width) in Reason::ALL.iter().zip(&widths) {
+                line.push_str(&format!("  {:>width$}", counts.get(reason).copied().unwrap_or(0), width = width));
+            }
+            out.push_str(&line);
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report serializes to JSON")
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Table => self.table(),
+            Format::Json => self.json(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const BATCH: &str = "eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277";
+
+    #[test]
+    fn test_report() {
+        let input = parse(BATCH);
+        let report = Report::new(&input, &Schema::default());
+        assert_eq!(report.valid, 1);
+        fn reasons(entry: &Entry) -> Vec<(&str, Reason)> {
+            entry.failures.iter().map(|f| (f.field.as_str(), f.reason)).collect()
+        }
+        assert_eq!(
+            reasons(&report.passports[0]),
+            vec![("eyr", Reason::OutOfRange), ("hgt", Reason::WrongUnit), ("pid", Reason::BadFormat)]
+        );
+        assert!(report.passports[1].valid);
+        assert_eq!(reasons(&report.passports[2]), vec![("hcl", Reason::BadFormat)]);
+        assert_eq!(report.reasons.get(&Reason::BadFormat), Some(&2));
+        assert_eq!(report.fields["hgt"].get(&Reason::WrongUnit), Some(&1));
+    }
+
+    #[test]
+    fn test_table() {
+        let input = parse(BATCH);
+        let table = Report::new(&input, &Schema::default()).table();
+        let expected = "\
+passport  valid  failures
+       1  no     eyr out-of-range `1972`, hgt wrong-unit `170`, pid bad-format `186cm`
+       2  yes
+       3  no     hcl bad-format `dab227`
+1 of 3 valid
+
+field  missing  unparseable  out-of-range  wrong-unit  bad-format
+eyr          0            0             1           0           0
+hcl          0            0             0           0           1
+hgt          0            0             0           1           0
+pid          0            0             0           0           1
+total        0            0             1           1           2
+";
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn test_json() {
+        let input = parse(BATCH);
+        let json: serde_json::Value = serde_json::from_str(&Report::new(&input, &Schema::default()).json()).expect("valid JSON");
+        assert_eq!(json["valid"], 1);
+        assert_eq!(json["passports"][0]["failures"][1]["reason"], "wrong-unit");
+        assert_eq!(json["reasons"]["bad-format"], 2);
+        assert_eq!(json["fields"]["hcl"]["bad-format"], 1);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!("json".parse::<Format>().unwrap(), Format::Json);
+        assert!(matches!("csv".parse::<Format>(), Err(Error::UnknownFormat { .. })));
+    }
+}
@@ -0,0 +1,459 @@
+use crate::passport::RawPassport;
+use crate::{Error, LoadingSchema, ParsingSchema};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// The puzzle's rules, see `schema.toml` for the format.
+const DEFAULT: &str = include_str!("../schema.toml");
+
+/// Inclusive bounds of an integer, optionally written with an exact number of digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bounds {
+    pub min: i64,
+    pub max: i64,
+    pub digits: Option<usize>,
+}
+
+impl Bounds {
+    /// Integers are plain ASCII digits with an optional leading `-`, so unlike `str::parse` a `+` sign,
+    /// whitespace or other scripts' digits are unparseable.
+    pub fn check(&self, value: &str) -> Result<(), Reason> {
+        let digits = value.strip_prefix('-').unwrap_or(value);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) || self.digits.is_some_and(|n| n != digits.len()) {
+            return Err(Reason::Unparseable);
+        }
+        // all digits, so the only possible failure is overflow, which is out of any range
+        let value = value.parse::<i64>().map_err(|_| Reason::OutOfRange)?;
+        if (self.min..=self.max).contains(&value) {
+            Ok(())
+        } else {
+            Err(Reason::OutOfRange)
+        }
+    }
+}
+
+/// Why a field was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Reason {
+    Missing,
+    Unparseable,
+    OutOfRange,
+    WrongUnit,
+    BadFormat,
+}
+
+impl Reason {
+    pub const ALL: [Reason; 5] = [Reason::Missing, Reason::Unparseable, Reason::OutOfRange, Reason::WrongUnit, Reason::BadFormat];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Reason::Missing => "missing",
+            Reason::Unparseable => "unparseable",
+            Reason::OutOfRange => "out-of-range",
+            Reason::WrongUnit => "wrong-unit",
+            Reason::BadFormat => "bad-format",
+        }
+    }
+}
+
+/// A field of a passport that broke the schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Failure {
+    pub field: String,
+    /// The rejected value, `None` when the field is missing
+    pub value: Option<String>,
+    pub reason: Reason,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{} {} `{}`", self.field, self.reason.name(), value),
+            None => write!(f, "{} {}", self.field, self.reason.name()),
+        }
+    }
+}
+
+/// What the value of a field has to look like.
+#[derive(Debug, Clone)]
+pub enum Rule {
+    /// Anything goes as long as the field is there
+    Any,
+    /// An integer within the bounds
+    Range(Bounds),
+    /// The whole value matches the regular expression
+    Pattern(Regex),
+    /// One of a fixed set of values
+    OneOf(Vec<String>),
+    /// An integer directly followed by one of the units, within that unit's bounds; when one unit
+    /// ends another, such as `m` and `mm`, the longest one the value ends with is used
+    Units(BTreeMap<String, Bounds>),
+}
+
+impl Rule {
+    pub fn check(&self, value: &str) -> Result<(), Reason> {
+        match self {
+            Rule::Any => Ok(()),
+            Rule::Range(bounds) => bounds.check(value),
+            Rule::Pattern(pattern) if pattern.is_match(value) => Ok(()),
+            Rule::OneOf(values) if values.iter().any(|v| v == value) => Ok(()),
+            Rule::Pattern(_) | Rule::OneOf(_) => Err(Reason::BadFormat),
+            Rule::Units(units) => units
+                .iter()
+                .filter(|(unit, _)| value.ends_with(unit.as_str()))
+                .max_by_key(|(unit, _)| unit.len())
+                .map_or(Err(Reason::WrongUnit), |(unit, bounds)| bounds.check(&value[..value.len() - unit.len()])),
+        }
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        self.check(value).is_ok()
+    }
+}
+
+/// A field as written in a schema file, before checking it names at most one rule.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct RawField {
+    name: String,
+    #[serde(default = "required_by_default")]
+    required: bool,
+    range: Option<Bounds>,
+    pattern: Option<String>,
+    one_of: Option<Vec<String>>,
+    units: Option<BTreeMap<String, Bounds>>,
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawField")]
+pub struct Field {
+    pub name: String,
+    pub required: bool,
+    pub rule: Rule,
+}
+
+impl TryFrom<RawField> for Field {
+    type Error = String;
+
+    fn try_from(raw: RawField) -> Result<Self, Self::Error> {
+        let RawField {
+            name,
+            required,
+            range,
+            pattern,
+            one_of,
+            units,
+        } = raw;
+        let mut rules = Vec::new();
+        if let Some(bounds) = range {
+            rules.push(Rule::Range(bounds));
+        }
+        if let Some(pattern) = pattern {
+            // anchored so the pattern has to match the whole value
+            let pattern = Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| format!("field `{}`: {}", name, e))?;
+            rules.push(Rule::Pattern(pattern));
+        }
+        if let Some(values) = one_of {
+            rules.push(Rule::OneOf(values));
+        }
+        if let Some(units) = units {
+            rules.push(Rule::Units(units));
+        }
+        if rules.len() > 1 {
+            return Err(format!("field `{}` has more than one rule", name));
+        }
+        Ok(Self {
+            name,
+            required,
+            rule: rules.pop().unwrap_or(Rule::Any),
+        })
+    }
+}
+
+/// The fields a passport may have and the rules for their values.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schema {
+    #[serde(rename = "field")]
+    pub fields: Vec<Field>,
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        DEFAULT.parse().expect("default schema is valid")
+    }
+}
+
+impl std::str::FromStr for Schema {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        toml::from_str(text).context(ParsingSchema)
+    }
+}
+
+impl Schema {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let text = fs::read_to_string(path.as_ref()).context(LoadingSchema {
+            path: path.as_ref().to_string_lossy(),
+        })?;
+        text.parse()
+    }
+
+    /// Whether every required field is present, whatever its value (part 1).
+    pub fn has_required(&self, passport: &RawPassport<'_>) -> bool {
+        self.fields.iter().all(|field| !field.required || passport.contains_key(&field.name))
+    }
+
+    /// Whether every required field is present and every field present satisfies its rule (part 2).
+    pub fn is_valid(&self, passport: &RawPassport<'_>) -> bool {
+        self.fields.iter().all(|field| match passport.get(&field.name) {
+            Some(value) => field.rule.accepts(value),
+            None => !field.required,
+        })
+    }
+
+    /// Every field breaking the schema, in schema order; empty exactly when [`Schema::is_valid`] holds.
+    pub fn validate(&self, passport: &RawPassport<'_>) -> Vec<Failure> {
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                let value = passport.get(&field.name);
+                let reason = match value {
+                    Some(value) => field.rule.check(value).err()?,
+                    None if field.required => Reason::Missing,
+                    None => return None,
+                };
+                Some(Failure {
+                    field: field.name.clone(),
+                    value: value.map(|value| value.to_string()),
+                    reason,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn rule(name: &str) -> Rule {
+        Schema::default().fields.into_iter().find(|field| field.name == name).expect("field").rule
+    }
+
+    #[test]
+    fn test_default_schema() {
+        let schema = Schema::default();
+        let names = schema.fields.iter().map(|field| field.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"]);
+        assert!(!schema.fields[7].required);
+        assert!(rule("byr").accepts("2002") && !rule("byr").accepts("2003"));
+        assert!(rule("hgt").accepts("60in") && rule("hgt").accepts("190cm"));
+        assert!(!rule("hgt").accepts("190in") && !rule("hgt").accepts("190") && !rule("hgt").accepts("in60"));
+        assert!(rule("hcl").accepts("#123abc") && !rule("hcl").accepts("#123abz") && !rule("hcl").accepts("123abc"));
+        assert!(rule("ecl").accepts("brn") && !rule("ecl").accepts("wat"));
+        assert!(rule("pid").accepts("000000001") && !rule("pid").accepts("0123456789"));
+        assert!(rule("cid").accepts("anything"));
+    }
+
+    #[test]
+    fn test_reasons() {
+        let schema = Schema::default();
+        assert_eq!(rule("byr").check("19x0"), Err(Reason::Unparseable));
+        assert_eq!(rule("byr").check("1919"), Err(Reason::OutOfRange));
+        assert_eq!(rule("hgt").check("170"), Err(Reason::WrongUnit));
+        assert_eq!(rule("hgt").check("tallcm"), Err(Reason::Unparseable));
+        assert_eq!(rule("hgt").check("200cm"), Err(Reason::OutOfRange));
+        assert_eq!(rule("hcl").check("#zzzzzz"), Err(Reason::BadFormat));
+        assert_eq!(rule("ecl").check("zzz"), Err(Reason::BadFormat));
+
+        let passport = [
+            ("byr", "2003"),
+            ("iyr", "2015"),
+            ("eyr", "2025"),
+            ("hgt", "60in"),
+            ("hcl", "#abcdef"),
+            ("ecl", "zzz"),
+        ]
+        .iter()
+        .copied()
+        .collect::<RawPassport<'_>>();
+        let failures = schema.validate(&passport);
+        let summary = failures.iter().map(|f| (f.field.as_str(), f.value.as_deref(), f.reason)).collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("byr", Some("2003"), Reason::OutOfRange),
+                ("ecl", Some("zzz"), Reason::BadFormat),
+                ("pid", None, Reason::Missing)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_boundaries() {
+        use Reason::*;
+        let cases: &[(&str, &str, Result<(), Reason>)] = &[
+            ("byr", "1919", Err(OutOfRange)),
+            ("byr", "1920", Ok(())),
+            ("byr", "2002", Ok(())),
+            ("byr", "2003", Err(OutOfRange)),
+            ("byr", "01980", Err(Unparseable)),
+            ("byr", "+1980", Err(Unparseable)),
+            ("byr", " 1980", Err(Unparseable)),
+            ("byr", "", Err(Unparseable)),
+            ("iyr", "2009", Err(OutOfRange)),
+            ("iyr", "2010", Ok(())),
+            ("iyr", "2020", Ok(())),
+            ("iyr", "2021", Err(OutOfRange)),
+            ("iyr", "２０１５", Err(Unparseable)),
+            ("eyr", "2019", Err(OutOfRange)),
+            ("eyr", "2020", Ok(())),
+            ("eyr", "2030", Ok(())),
+            ("eyr", "2031", Err(OutOfRange)),
+            ("eyr", "-2025", Err(OutOfRange)),
+            ("hgt", "149cm", Err(OutOfRange)),
+            ("hgt", "150cm", Ok(())),
+            ("hgt", "193cm", Ok(())),
+            ("hgt", "194cm", Err(OutOfRange)),
+            ("hgt", "58in", Err(OutOfRange)),
+            ("hgt", "59in", Ok(())),
+            ("hgt", "76in", Ok(())),
+            ("hgt", "77in", Err(OutOfRange)),
+            ("hgt", "190in", Err(OutOfRange)),
+            ("hgt", "in60", Err(WrongUnit)),
+            ("hgt", "60", Err(WrongUnit)),
+            ("hgt", "60 in", Err(Unparseable)),
+            ("hgt", "+60in", Err(Unparseable)),
+            ("hgt", "cm", Err(Unparseable)),
+            ("hgt", "60IN", Err(WrongUnit)),
+            ("hgt", "99999999999999999999cm", Err(OutOfRange)),
+            ("hcl", "#123abc", Ok(())),
+            ("hcl", "#000000", Ok(())),
+            ("hcl", "#123abz", Err(BadFormat)),
+            ("hcl", "#zzzzzz", Err(BadFormat)),
+            ("hcl", "#123ABC", Err(BadFormat)),
+            ("hcl", "#123abc0", Err(BadFormat)),
+            ("hcl", "#123ab", Err(BadFormat)),
+            ("hcl", "123abc", Err(BadFormat)),
+            ("hcl", "#123abc\n", Err(BadFormat)),
+            ("ecl", "amb", Ok(())),
+            ("ecl", "oth", Ok(())),
+            ("ecl", "wat", Err(BadFormat)),
+            ("ecl", "AMB", Err(BadFormat)),
+            ("ecl", "ambblu", Err(BadFormat)),
+            ("pid", "000000001", Ok(())),
+            ("pid", "0123456789", Err(BadFormat)),
+            ("pid", "12345678", Err(BadFormat)),
+            ("pid", "12345678a", Err(BadFormat)),
+            ("pid", "١٢٣٤٥٦٧٨٩", Err(BadFormat)),
+            ("cid", "", Ok(())),
+        ];
+        for (field, value, expected) in cases {
+            assert_eq!(rule(field).check(value), *expected, "{}:{}", field, value);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_years(year in 0i64..10000) {
+            let text = format!("{:04}", year);
+            prop_assert_eq!(rule("byr").accepts(&text), (1920..=2002).contains(&year));
+            prop_assert_eq!(rule("eyr").accepts(&text), (2020..=2030).contains(&year));
+            let padded = format!("0{}", text);
+            prop_assert!(!rule("iyr").accepts(&padded));
+        }
+
+        #[test]
+        fn test_heights(value in 0i64..1000, unit in "cm|in|mm|") {
+            let expected = match unit.as_str() {
+                "cm" => (150..=193).contains(&value),
+                "in" => (59..=76).contains(&value),
+                _ => false,
+            };
+            prop_assert_eq!(rule("hgt").accepts(&format!("{}{}", value, unit)), expected);
+            let reversed = format!("{}{}", unit, value);
+            prop_assert!(!rule("hgt").accepts(&reversed) || unit.is_empty() && expected);
+        }
+
+        #[test]
+        fn test_hair_colours(value in "#?[0-9a-gA-F]{5,7}") {
+            let expected = value.len() == 7 && value.starts_with('#') && value[1..].bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+            prop_assert_eq!(rule("hcl").accepts(&value), expected);
+        }
+
+        #[test]
+        fn test_passport_ids(value in "[0-9a]{7,11}") {
+            let expected = value.len() == 9 && value.bytes().all(|b| b.is_ascii_digit());
+            prop_assert_eq!(rule("pid").accepts(&value), expected);
+        }
+    }
+
+    #[test]
+    fn test_custom_schema() {
+        let schema: Schema = r#"
+[[field]]
+name = "age"
+range = { min = 18, max = 99 }
+
+[[field]]
+name = "nick"
+required = false
+pattern = "[a-z]+"
+"#
+        .parse()
+        .expect("schema");
+        let passport = |pairs: &[(&'static str, &'static str)]| pairs.iter().copied().collect::<RawPassport<'_>>();
+        assert!(schema.is_valid(&passport(&[("age", "30")])));
+        assert!(schema.is_valid(&passport(&[("age", "30"), ("nick", "bob"), ("other", "x")])));
+        assert!(!schema.is_valid(&passport(&[("age", "30"), ("nick", "Bob")])));
+        assert!(!schema.is_valid(&passport(&[("nick", "bob")])));
+        assert!(schema.has_required(&passport(&[("age", "3")])));
+    }
+
+    #[test]
+    fn test_overlapping_units() {
+        let schema: Schema = r#"
+[[field]]
+name = "length"
+units = { m = { min = 1, max = 5 }, mm = { min = 10, max = 5000 }, n = { min = 0, max = 0 }, in = { min = 1, max = 200 } }
+"#
+        .parse()
+        .expect("schema");
+        let rule = &schema.fields[0].rule;
+        assert_eq!(rule.check("5m"), Ok(()));
+        assert_eq!(rule.check("5mm"), Err(Reason::OutOfRange));
+        assert_eq!(rule.check("50mm"), Ok(()));
+        assert_eq!(rule.check("60in"), Ok(()));
+        assert_eq!(rule.check("0n"), Ok(()));
+        assert_eq!(rule.check("xmm"), Err(Reason::Unparseable));
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        for text in [
+            "[[field]]\nname = \"a\"\nrange = { min = 1, max = 2 }\npattern = \"x\"",
+            "[[field]]\nname = \"a\"\npattern = \"(\"",
+            "[[field]]\nname = \"a\"\nlength = 3",
+            "[[fields]]\nname = \"a\"",
+        ]
+        .iter()
+        {
+            assert!(matches!(text.parse::<Schema>(), Err(Error::ParsingSchema { .. })), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::{Error, InvalidId, InvalidPart};
+use parse::Line;
+use std::cmp::Ordering;
+use std::str::CharIndices;
+use std::str::FromStr;
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Seat {
+    pub row: usize,
+    pub col: usize,
+    pub id: usize,
+}
+
+impl Ord for Seat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl PartialOrd for Seat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The row or column half of a boarding pass: `bits` letters, each `lower` for the lower half of
+/// the remaining range or `upper` for the upper half, which is reading them as binary digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Part {
+    pub lower: char,
+    pub upper: char,
+    pub bits: u32,
+}
+
+impl Part {
+    pub const fn new(lower: char, upper: char, bits: u32) -> Self {
+        Self { lower, upper, bits }
+    }
+
+    /// Number of rows or columns the letters can address.
+    pub fn size(&self) -> usize {
+        1 << self.bits
+    }
+
+    fn read(&self, line: Line<'_>, letters: &mut CharIndices<'_>, len: usize) -> Result<usize, parse::Error> {
+        let mut value = 0;
+        for _ in 0..self.bits {
+            let (offset, c) = letters.next().ok_or_else(|| line.error_at_end(format!("seat of {} characters", len)))?;
+            let bit = match c {
+                _ if c == self.lower => 0,
+                _ if c == self.upper => 1,
+                _ => return Err(line.error_at(offset, c.len_utf8(), format!("`{}` or `{}`", self.lower, self.upper))),
+            };
+            value = value << 1 | bit;
+        }
+        Ok(value)
+    }
+}
+
+/// Parses the lower and upper letters followed by the number of letters, such as `FB7`.
+impl FromStr for Part {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut chars = spec.chars();
+        match (chars.next(), chars.next(), chars.as_str().parse::<u32>()) {
+            (Some(lower), Some(upper), Ok(bits)) if lower != upper && (1..=16).contains(&bits) => Ok(Self::new(lower, upper, bits)),
+            _ => InvalidPart { spec }.fail(),
+        }
+    }
+}
+
+/// Seat id as `row * self.row + column * self.column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Id {
+    pub row: usize,
+    pub column: usize,
+}
+
+/// Parses the row and column multipliers separated by a comma, such as `8,1`.
+impl FromStr for Id {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let id = spec.split_once(',').and_then(|(row, column)| Some((row.parse().ok()?, column.parse().ok()?)));
+        match id {
+            Some((row, column)) => Ok(Self { row, column }),
+            None => InvalidId { spec }.fail(),
+        }
+    }
+}
+
+/// How boarding passes encode seats: the row letters, then the column letters, and the id formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub rows: Part,
+    pub columns: Part,
+    pub id: Id,
+}
+
+/// The puzzle's plane: 128 rows as `F`/`B`, 8 columns as `L`/`R` and ids of `row * 8 + column`.
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new(Part::new('F', 'B', 7), Part::new('L', 'R', 3))
+    }
+}
+
+impl Layout {
+    /// A layout numbering seats row by row, so the id is the whole pass read as one binary number.
+    pub fn new(rows: Part, columns: Part) -> Self {
+        Self {
+            rows,
+            columns,
+            id: Id {
+                row: columns.size(),
+                column: 1,
+            },
+        }
+    }
+
+    /// Number of letters in a boarding pass.
+    pub fn letters(&self) -> usize {
+        (self.rows.bits + self.columns.bits) as usize
+    }
+
+    /// Decodes a boarding pass, pointing at the first letter that does not belong where it is,
+    /// at the end of a pass of the wrong length, or at the whole pass when its id overflows.
+    pub fn decode(&self, line: Line<'_>) -> Result<Seat, parse::Error> {
+        let mut letters = line.text().char_indices();
+        let row = self.rows.read(line, &mut letters, self.letters())?;
+        let col = self.columns.read(line, &mut letters, self.letters())?;
+        if let Some((offset, _)) = letters.next() {
+            return Err(line.error_at(offset, line.text().len() - offset, format!("seat of {} characters", self.letters())));
+        }
+        let id = row
+            .checked_mul(self.id.row)
+            .zip(col.checked_mul(self.id.column))
+            .and_then(|(row, col)| row.checked_add(col))
+            .ok_or_else(|| line.error(line.text(), format!("seat with an id up to {}", usize::MAX)))?;
+        Ok(Seat { row, col, id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(layout: &Layout, pass: &str) -> Result<Seat, parse::Error> {
+        layout.decode(Line::new(1, pass))
+    }
+
+    fn encode(layout: &Layout, row: usize, col: usize) -> String {
+        let letters = |part: &Part, value: usize| {
+            (0..part.bits)
+                .rev()
+                .map(|bit| if value >> bit & 1 == 1 { part.upper } else { part.lower })
+                .collect::<String>()
+        };
+        letters(&layout.rows, row) + &letters(&layout.columns, col)
+    }
+
+    #[test]
+    fn test_decode() {
+        let layout = Layout::default();
+        assert_eq!(decode(&layout, "FBFBBFFRLR").unwrap(), Seat { row: 44, col: 5, id: 357 });
+        assert_eq!(decode(&layout, "BBFFBBFRLL").unwrap(), Seat { row: 102, col: 4, id: 820 });
+        for row in 0..128 {
+            for col in 0..8 {
+                let seat = decode(&layout, &encode(&layout, row, col)).unwrap();
+                assert_eq!(seat, Seat { row, col, id: row * 8 + col });
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_layout() {
+        let mut layout = Layout::new("ab2".parse().unwrap(), "011".parse().unwrap());
+        assert_eq!((layout.letters(), layout.id), (3, Id { row: 2, column: 1 }));
+        layout.id = "1,4".parse().unwrap();
+        assert_eq!(decode(&layout, "ba1").unwrap(), Seat { row: 2, col: 1, id: 6 });
+        layout.id = Id { row: usize::MAX, column: 1 };
+        assert_eq!(decode(&layout, "ab0").unwrap().id, usize::MAX);
+        let error = decode(&layout, "ba1").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (1, "ba1"));
+        let wide = Layout::new(Part::new('F', 'B', 10), Part::new('L', 'R', 6));
+        assert_eq!(decode(&wide, &encode(&wide, 1000, 60)).unwrap().id, 1000 * 64 + 60);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let layout = Layout::default();
+        let error = decode(&layout, "FBFBBFLRLR").unwrap_err();
+        assert_eq!((error.column, error.found.as_str(), error.expected.as_str()), (7, "L", "`F` or `B`"));
+        let error = decode(&layout, "FBFBBFFRBR").unwrap_err();
+        assert_eq!((error.column, error.expected.as_str()), (9, "`L` or `R`"));
+        let error = decode(&layout, "FBFBBFFRL").unwrap_err();
+        assert_eq!((error.column, error.found.as_str(), error.expected.as_str()), (10, "", "seat of 10 characters"));
+        let error = decode(&layout, "FBFBBFFRLRLL").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (11, "LL"));
+    }
+
+    #[test]
+    fn test_specs() {
+        assert_eq!("FB7".parse::<Part>().unwrap(), Part::new('F', 'B', 7));
+        for spec in ["FB", "FF7", "FB0", "FB17", "F7", "FBx"].iter() {
+            assert!(matches!(spec.parse::<Part>(), Err(Error::InvalidPart { .. })), "{}", spec);
+        }
+        assert_eq!("8,1".parse::<Id>().unwrap(), Id { row: 8, column: 1 });
+        for spec in ["8", "8,", "a,1", "8;1"].iter() {
+            assert!(matches!(spec.parse::<Id>(), Err(Error::InvalidId { .. })), "{}", spec);
+        }
+    }
+}
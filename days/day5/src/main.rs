@@ -1,82 +1,44 @@
-use input::Input;
-use snafu::{ResultExt, Snafu};
-use snafu_cli_debug::SnafuCliDebug;
-use std::cmp::{Ord, Ordering};
-use std::ops::Range;
+use bsp::{Layout, Part, Seat};
 
-#[derive(Snafu, SnafuCliDebug)]
-pub enum Error {
-    /// Error loading input
-    LoadingInput { source: input::Error },
-    /// Error converting string to integer
-    Parsing { source: std::num::ParseIntError },
-}
-
-#[derive(Debug, Eq, PartialEq)]
-struct Seat {
-    row: usize,
-    col: usize,
-    id: usize,
-}
+mod bsp;
 
-impl Ord for Seat {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.id.cmp(&other.id)
-    }
+error::day_error! {
+    #[snafu(display("Invalid seat letters `{}`, expected the lower and upper letter then a count from 1 to 16 such as `FB7`", spec))]
+    InvalidPart { spec: String },
+    #[snafu(display("Invalid seat id formula `{}`, expected row and column multipliers such as `8,1`", spec))]
+    InvalidId { spec: String },
 }
 
-impl PartialOrd for Seat {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+fn parse(input: &str, layout: &Layout) -> Result<Vec<Seat>, Error> {
+    Ok(parse::lines(input).map(|line| layout.decode(line)).collect::<Result<Vec<_>, parse::Error>>()?)
 }
 
-fn parse(input: &str) -> Vec<&str> {
-    input.lines().collect::<Vec<_>>()
+fn part1(input: &[Seat]) -> usize {
+    input.iter().max().map(|seat| seat.id).unwrap_or(0)
 }
 
-fn half(range: &Range<usize>) -> usize {
-    range.start + (range.end - range.start) / 2
+/// The first id missing between two taken seats, or 0 when there is no gap.
+fn part2(input: &[Seat]) -> usize {
+    let mut seats = input.iter().collect::<Vec<_>>();
+    seats.sort();
+    seats
+        .windows(2)
+        .find(|pair| pair[0].id.checked_add(1).is_some_and(|after| after < pair[1].id))
+        .map_or(0, |pair| pair[0].id + 1)
 }
 
-fn find_seat(seat: &str, rows: Range<usize>, columns: Range<usize>) -> Seat {
-    let (rows, columns) = match seat.chars().next() {
-        Some('B') => (half(&rows)..rows.end, columns),
-        Some('F') => (rows.start..half(&rows), columns),
-        Some('L') => (rows, columns.start..half(&columns)),
-        Some('R') => (rows, half(&columns)..columns.end),
-        None => {
-            assert_eq!(rows.len(), 1);
-            assert_eq!(columns.len(), 1);
-            let row = rows.start;
-            let col = columns.start;
-            return Seat { row, col, id: row * 8 + col };
-        }
-        _ => unreachable!(),
+fn main() -> Result<(), Error> {
+    let part = |name: &str, default: Part| match cli::flags(name).last() {
+        Some(spec) => spec.parse::<Part>(),
+        None => Ok(default),
     };
-    find_seat(&seat[1..], rows, columns)
-}
-
-fn part1(input: &[&str]) -> usize {
-    input.iter().map(|seat| find_seat(seat, 0..128, 0..8)).max().map(|seat| seat.id).unwrap_or(0)
-}
-
-fn part2(input: &[&str]) -> usize {
-    let mut ids = input.iter().map(|seat| find_seat(seat, 0..128, 0..8)).collect::<Vec<_>>();
-    ids.sort();
-    let mut prev = ids[0].id;
-    for seat in &ids[1..] {
-        if seat.id - 1 != prev {
-            return seat.id - 1;
-        }
-        prev = seat.id;
+    let defaults = Layout::default();
+    let mut layout = Layout::new(part("rows", defaults.rows)?, part("columns", defaults.columns)?);
+    if let Some(spec) = cli::flags("id").last() {
+        layout.id = spec.parse()?;
     }
-    0
-}
-
-fn main() -> Result<(), Error> {
-    let input = Input::open("config.toml").context(LoadingInput)?.get(5).context(LoadingInput)?;
-    let input = parse(&input);
+    let input = error::load(5)?;
+    let input = parse(&input, &layout)?;
     println!("Part 1 {}", part1(&input));
     println!("Part 2 {}", part2(&input));
     Ok(())
@@ -92,11 +54,11 @@ mod tests {
 BFFFBBFRRR
 FFFBBBFRRR
 BBFFBBFRLL"#;
-        let input = parse(test_input);
-        assert_eq!(find_seat(input[0], 0..128, 0..8), Seat { row: 44, col: 5, id: 357 });
-        assert_eq!(find_seat(input[1], 0..128, 0..8), Seat { row: 70, col: 7, id: 567 });
-        assert_eq!(find_seat(input[2], 0..128, 0..8), Seat { row: 14, col: 7, id: 119 });
-        assert_eq!(find_seat(input[3], 0..128, 0..8), Seat { row: 102, col: 4, id: 820 });
+        let input = parse(test_input, &Layout::default()).expect("parse");
+        assert_eq!(input[0], Seat { row: 44, col: 5, id: 357 });
+        assert_eq!(input[1], Seat { row: 70, col: 7, id: 567 });
+        assert_eq!(input[2], Seat { row: 14, col: 7, id: 119 });
+        assert_eq!(input[3], Seat { row: 102, col: 4, id: 820 });
     }
 
     #[test]
@@ -105,7 +67,35 @@ BBFFBBFRLL"#;
 BFFFBBFRRR
 FFFBBBFRRR
 BBFFBBFRLL"#;
-        let input = parse(test_input);
+        let input = parse(test_input, &Layout::default()).expect("parse");
         assert_eq!(part1(&input), 820)
     }
+
+    #[test]
+    fn test_part2() {
+        let layout = Layout {
+            id: "1,4".parse().unwrap(),
+            ..Layout::new(Part::new('a', 'b', 2), Part::new('0', '1', 1))
+        };
+        let input = parse("aa0\nab0\nbb0\naa1", &layout).expect("parse");
+        assert_eq!(part2(&input), 2);
+        let columns = Layout {
+            id: "0,1".parse().unwrap(),
+            ..Layout::default()
+        };
+        let input = parse("FFFFFFFLLL\nFFFFFFBLLL", &columns).expect("parse");
+        assert_eq!(part2(&input), 0);
+        assert_eq!(part2(&[]), 0);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        match parse("FBFBBFFRLR\nFBFBXFFRLR", &Layout::default()) {
+            Err(Error::Solver {
+                source: error::Error::Parsing { source },
+            }) => assert_eq!((source.line, source.column, source.found.as_str()), (2, 5, "X")),
+            _ => panic!("expected parse error"),
+        }
+        assert!(parse("FBFBBFFRL", &Layout::default()).is_err());
+    }
 }
@@ -1,29 +1,10 @@
-use input::Input;
-use snafu::{ResultExt, Snafu};
-use snafu_cli_debug::SnafuCliDebug;
+use error::Error;
 use std::collections::HashSet;
 
-#[derive(Snafu, SnafuCliDebug)]
-pub enum Error {
-    /// Error loading input
-    LoadingInput { source: input::Error },
-    /// Error converting string to integer
-    Parsing { source: std::num::ParseIntError },
-}
-
 fn parse(input: &str) -> Vec<Vec<HashSet<char>>> {
-    let mut rtn = Vec::new();
-    let mut current_vec = Vec::new();
-    for line in input.lines() {
-        if line.is_empty() {
-            rtn.push(current_vec);
-            current_vec = Vec::new()
-        } else {
-            current_vec.push(line.chars().collect::<HashSet<_>>());
-        }
-    }
-    rtn.push(current_vec);
-    rtn
+    parse::records(input)
+        .map(|record| record.lines().map(|line| line.text().trim().chars().collect::<HashSet<_>>()).collect())
+        .collect()
 }
 
 fn part1(input: &[Vec<HashSet<char>>]) -> usize {
@@ -46,7 +27,7 @@ fn part2(input: &[Vec<HashSet<char>>]) -> usize {
 }
 
 fn main() -> Result<(), Error> {
-    let input = Input::open("config.toml").context(LoadingInput)?.get(6).context(LoadingInput)?;
+    let input = error::load(6)?;
     let input = parse(&input);
     println!("Part 1 {}", part1(&input));
     println!("Part 2 {}", part2(&input));
@@ -98,4 +79,12 @@ b"#;
         let input = parse(test_input);
         assert_eq!(part2(&input), 6);
     }
+
+    #[test]
+    fn test_parse_trailing_blank_lines() {
+        let input = parse("ab\r\nac\r\n\r\n\r\nb\r\n\r\n");
+        assert_eq!(input.len(), 2);
+        assert_eq!(part1(&input), 4);
+        assert_eq!(part2(&input), 2);
+    }
 }
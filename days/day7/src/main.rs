@@ -1,56 +1,35 @@
-use input::Input;
+use error::Error;
+use parse::Line;
 use petgraph::graphmap::DiGraphMap;
 use petgraph::Direction;
-use regex::Regex;
-use snafu::{OptionExt, ResultExt, Snafu};
-use snafu_cli_debug::SnafuCliDebug;
 use std::collections::{HashMap, HashSet};
 
-#[derive(Snafu, SnafuCliDebug)]
-pub enum Error {
-    /// Error loading input
-    LoadingInput { source: input::Error },
-    /// Error converting string to integer
-    Parsing { source: std::num::ParseIntError },
-    /// Regex Error
-    CompilingRegex { source: regex::Error },
-    /// MissingName
-    MissingName,
-}
-
 struct Bag<'a> {
     name: &'a str,
     bags: HashMap<&'a str, usize>,
 }
 
-fn parse_line(mut line: &str) -> Result<Bag, Error> {
-    let first = Regex::new(r#"^(.+)(?: bags contain)"#).context(CompilingRegex)?;
-    let second = Regex::new(r#"^\s?(\d+) (.*) bag"#).context(CompilingRegex)?;
-    let mut name = None;
-
-    if let Some(captures) = first.captures(line) {
-        name = Some(captures.get(1).context(MissingName)?.as_str());
-        line = &line[captures[0].len()..];
-    }
-    let name = name.context(MissingName)?;
-    let bags = line
-        .split(',')
-        .filter_map(|s| {
-            if let Some(captures) = second.captures(s) {
-                let bag_name = captures.get(2).expect("No capture").as_str();
-                let count = (&captures[1]).parse::<usize>().expect("No paarse");
-                Some((bag_name, count))
-            } else {
-                None
-            }
-        })
-        .collect::<HashMap<_, _>>();
+fn parse_line(line: Line<'_>) -> Result<Bag<'_>, parse::Error> {
+    let mut cursor = line.cursor();
+    let name = cursor.until(" bags contain ")?;
+    cursor.tag(" bags contain ")?;
+    let bags = if cursor.eat("no other bags") {
+        HashMap::new()
+    } else {
+        cursor
+            .delimited(", ", |c| c.quantity("bag"))?
+            .into_iter()
+            .map(|(count, name)| (name, count))
+            .collect::<HashMap<_, _>>()
+    };
+    cursor.tag(".")?;
+    cursor.end()?;
     Ok(Bag { name, bags })
 }
 
 fn parse(input: &str) -> Result<DiGraphMap<&str, usize>, Error> {
     let mut graph = DiGraphMap::new();
-    let bags = input.lines().map(|l| parse_line(l)).collect::<Result<Vec<_>, Error>>()?;
+    let bags = parse::lines(input).map(parse_line).collect::<Result<Vec<_>, _>>()?;
     bags.iter().for_each(|bag| {
         graph.add_node(bag.name);
         for (name, count) in bag.bags.iter() {
@@ -89,7 +68,7 @@ fn part1(input: &DiGraphMap<&str, usize>) -> usize {
 }
 
 fn main() -> Result<(), Error> {
-    let input = Input::open("config.toml").context(LoadingInput)?.get(7).context(LoadingInput)?;
+    let input = error::load(7)?;
     let input = parse(&input)?;
     println!("Part 1 {}", part1(&input));
     println!("Part 2 {}", part2(&input));
@@ -142,4 +121,12 @@ dark violet bags contain no other bags."#;
         let input = parse(test_input).expect("parse");
         assert_eq!(part2(&input), 126);
     }
+
+    #[test]
+    fn test_parse_error() {
+        match parse("light red bags contain 1 bright white bag.\nshiny gold contain 1 dark olive bag.") {
+            Err(Error::Parsing { source }) => assert_eq!((source.line, source.column), (2, 1)),
+            _ => panic!("expected parse error"),
+        }
+    }
 }
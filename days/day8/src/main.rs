@@ -1,21 +1,10 @@
-use input::Input;
-use snafu::{ResultExt, Snafu};
-use snafu_cli_debug::SnafuCliDebug;
-
-#[derive(Snafu, SnafuCliDebug)]
-pub enum Error {
-    /// Error loading input
-    LoadingInput { source: input::Error },
-    /// Error converting string to integer
-    Parsing { source: std::num::ParseIntError },
-}
+use error::Error;
 
 #[derive(Debug, Clone)]
 enum Op {
     Nop,
     Jmp,
     Acc,
-    Unsupported,
 }
 
 #[derive(Debug, Clone)]
@@ -40,7 +29,6 @@ fn process(mut input: Vec<Instruction>) -> (bool, isize) {
             }
             Op::Nop => ip += 1,
             Op::Jmp => ip += instruction.value,
-            _ => unimplemented!(),
         }
         instruction.visited = true;
         if ip as usize == input.len() {
@@ -49,21 +37,23 @@ fn process(mut input: Vec<Instruction>) -> (bool, isize) {
     }
 }
 
-fn parse(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(|l| {
-            let mut instr = l.split(' ');
-            let op = match instr.next().unwrap() {
-                "jmp" => Op::Jmp,
-                "nop" => Op::Nop,
-                "acc" => Op::Acc,
-                _ => Op::Unsupported,
+fn parse(input: &str) -> Result<Vec<Instruction>, Error> {
+    Ok(parse::lines(input)
+        .map(|line| {
+            let mut cursor = line.cursor();
+            let op = match cursor.word() {
+                Ok("jmp") => Op::Jmp,
+                Ok("nop") => Op::Nop,
+                Ok("acc") => Op::Acc,
+                Ok(op) => return Err(line.error(op, "`acc`, `jmp` or `nop`")),
+                Err(_) => return Err(cursor.error("`acc`, `jmp` or `nop`")),
             };
-            let value = instr.next().unwrap().parse::<isize>().unwrap();
-            Instruction { op, value, visited: false }
+            cursor.tag(" ")?;
+            let value = cursor.signed::<isize>()?;
+            cursor.end()?;
+            Ok(Instruction { op, value, visited: false })
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?)
 }
 
 fn part1(input: &[Instruction]) -> isize {
@@ -101,8 +91,8 @@ fn part2(input: &[Instruction]) -> isize {
 }
 
 fn main() -> Result<(), Error> {
-    let input = Input::open("config.toml").context(LoadingInput)?.get(8).context(LoadingInput)?;
-    let input = parse(&input);
+    let input = error::load(8)?;
+    let input = parse(&input)?;
     println!("Part 1 {}", part1(&input));
     eprintln!("Part 2 {}", part2(&input));
     Ok(())
@@ -123,7 +113,7 @@ acc -99
 acc +1
 jmp -4
 acc +6"#;
-        let input = parse(test_input);
+        let input = parse(test_input).expect("parse");
         let value = part1(&input);
         assert_eq!(value, 5);
     }
@@ -139,8 +129,18 @@ acc -99
 acc +1
 jmp -4
 acc +6"#;
-        let input = parse(test_input);
+        let input = parse(test_input).expect("parse");
         let value = part2(&input);
         assert_eq!(value, 8);
     }
+
+    #[test]
+    fn test_parse_error() {
+        for (test_input, column) in &[("nop +0\nmul +1", 1), ("nop +0\nacc x1", 5), ("nop +0\nacc", 4)] {
+            match parse(test_input) {
+                Err(Error::Parsing { source }) => assert_eq!((source.line, source.column), (2, *column)),
+                _ => panic!("expected parse error"),
+            }
+        }
+    }
 }